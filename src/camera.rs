@@ -1,6 +1,8 @@
-use crate::types::{Point3, Ray, Vect3};
-use rand::{thread_rng, Rng};
-use rand_distr::Uniform;
+use crate::sampler;
+use crate::surfaces::Surface;
+use crate::types::{Mat3, Point3, Ray, Vect3};
+use rand::Rng;
+use rand_distr::{Uniform, UnitDisc};
 use std::f32::consts::PI;
 
 /// Pick a random point in an isosceles triangle that is
@@ -8,27 +10,83 @@ use std::f32::consts::PI;
 ///
 /// See <https://mathworld.wolfram.com/TrianglePointPicking.html>.
 fn rand_point_in_triangle(angle: f32) -> Vect3 {
-    let mut rng = thread_rng();
+    let mut rng = sampler::rng();
     let up = Vect3((angle / 2.0).cos(), (angle / 2.0).sin(), 0.0);
     let down = Vect3(up.x(), -up.y(), up.z());
     let u = rng.sample(Uniform::new_inclusive(0.0, 1.0));
     let v = rng.sample(Uniform::new_inclusive(0.0, 1.0));
     let point = u * up + v * down;
     if point.x() > up.x() {
-        Vect3(up.x(), 0.0, 0.0) - point
+        // Reflect points in the far half of the parallelogram spanned
+        // by `up` and `down` back into the triangle.
+        (up + down) - point
     } else {
         point
     }
 }
 
+/// Rotate a vector by a rotation vector (i.e. around its axis, by an angle
+/// equal to its length), using Rodrigues' rotation formula.
+fn rotate(vector: Vect3, rotation: Vect3) -> Vect3 {
+    let angle = rotation.norm();
+    if angle == 0.0 {
+        return vector;
+    }
+    let axis = rotation / angle;
+    (vector * angle.cos())
+        + (axis.cross(vector) * angle.sin())
+        + (axis * axis.dot(vector) * (1.0 - angle.cos()))
+}
+
+/// An axis-aligned rectangle in pixel coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub min: (f32, f32),
+    pub max: (f32, f32),
+}
+
+impl Rect {
+    /// Get the width of the rectangle.
+    pub fn width(&self) -> f32 {
+        self.max.0 - self.min.0
+    }
+
+    /// Get the height of the rectangle.
+    pub fn height(&self) -> f32 {
+        self.max.1 - self.min.1
+    }
+
+    /// Get the center point of the rectangle.
+    pub fn center(&self) -> (f32, f32) {
+        (
+            (self.min.0 + self.max.0) / 2.0,
+            (self.min.1 + self.max.1) / 2.0,
+        )
+    }
+}
+
+/// The projection used by a camera to map viewport positions to rays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Projection {
+    /// A pinhole (or thin lens) perspective projection.
+    Perspective,
+    /// A 360° equirectangular (longitude/latitude) projection.
+    Equirectangular,
+}
+
 /// A camera abstraction.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Camera {
+    projection: Projection,
     origin: Point3,
     corner: Point3,
     camera_cs: (Vect3, Vect3, Vect3),
     image_plane: (Vect3, Vect3),
     radius: f32,
+    aperture_sides: u32,
+    velocity: Vect3,
+    angular: Vect3,
+    shutter: (f32, f32),
 }
 
 impl Camera {
@@ -68,17 +126,146 @@ impl Camera {
         let corner = origin - (horiz / 2.0) - (vert / 2.0) - (z * focus_dist);
         // Construct the camera!
         Camera {
+            projection: Projection::Perspective,
             origin,
             corner,
             camera_cs: (x, y, z),
             image_plane: (horiz, vert),
             radius,
+            aperture_sides: 5,
+            velocity: Vect3::zero(),
+            angular: Vect3::zero(),
+            shutter: (0.0, 0.0),
         }
     }
 
-    /// Sample a singe point for a regular polygon aperture.
+    /// Create a new camera from a (diagonal) angle of view.
+    ///
+    /// The viewport is two units high, and as wide as given by the aspect
+    /// ratio. Since the angle of view is measured along the diagonal of the
+    /// viewport, the horizontal and vertical angles depend on the aspect ratio.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - the origin of the camera
+    /// * `target` - the point at which the camera is focused
+    /// * `vertical` - the upward direction of the camera
+    /// * `fov_degrees` - the diagonal angle of view, in degrees
+    /// * `aperture` - the aperture of the camera, in f-stops
+    /// * `aspect_ratio` - the aspect ratio (width over height) of the viewport
+    pub fn with_fov(
+        origin: Point3,
+        target: Point3,
+        vertical: Vect3,
+        fov_degrees: f32,
+        aperture: f32,
+        aspect_ratio: f32,
+    ) -> Camera {
+        let viewport = (2.0 * aspect_ratio, 2.0_f32);
+        // The angle of view can be determined using the diagonal image plane dimension `d`
+        // and the focal length `f`, using the relation `aov = 2 * arctan(d / 2f)`. Working
+        // backwards, we can calculate `f` as `f = d / (2 * tan(aov / 2))`.
+        let diagonal = (viewport.0.powi(2) + viewport.1.powi(2)).sqrt();
+        let focal_length = (diagonal / 2.0) / (fov_degrees.to_radians() / 2.0).tan();
+        Camera::new(origin, target, vertical, focal_length, aperture, viewport)
+    }
+
+    /// Create a new panoramic (equirectangular 360°) camera.
+    ///
+    /// The camera looks along `-z`, with `+y` pointing upwards. The horizontal
+    /// viewport position is mapped to the longitude, all the way around the
+    /// camera (with the forward direction in the middle), and the vertical
+    /// viewport position is mapped to the latitude, from straight down to
+    /// straight up. The viewport thus has an aspect ratio of two.
+    ///
+    /// Panoramic cameras have no lens, so everything is in focus. The matrices
+    /// (and projections) of the camera assume a perspective projection, so are
+    /// meaningless for panoramic cameras.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - the origin of the camera
+    pub fn panoramic(origin: Point3) -> Camera {
+        let (x, y, z) = (
+            Vect3(1.0, 0.0, 0.0),
+            Vect3(0.0, 1.0, 0.0),
+            Vect3(0.0, 0.0, 1.0),
+        );
+        Camera {
+            projection: Projection::Equirectangular,
+            origin,
+            corner: origin - z,
+            camera_cs: (x, y, z),
+            image_plane: (x * (2.0 * PI), y * PI),
+            radius: 0.0,
+            aperture_sides: 0,
+            velocity: Vect3::zero(),
+            angular: Vect3::zero(),
+            shutter: (0.0, 0.0),
+        }
+    }
+
+    /// Change the shape of the aperture, which is pentagonal by default.
+    ///
+    /// The aperture is a regular polygon with `sides` sides, or a perfect
+    /// circle if `sides` is zero (or otherwise too small to form a polygon).
+    pub fn with_aperture_sides(self, sides: u32) -> Camera {
+        Camera {
+            aperture_sides: sides,
+            ..self
+        }
+    }
+
+    /// Make the camera move while the shutter is open, causing motion blur.
+    ///
+    /// The camera pose given when constructing the camera is its pose at
+    /// time `0.0`. At time `t`, the camera has moved by `velocity * t` and
+    /// rotated (around its origin) by the rotation vector `angular * t`.
+    ///
+    /// # Arguments
+    ///
+    /// * `velocity` - linear velocity of the camera
+    /// * `angular` - angular velocity of the camera (in radians per unit time)
+    /// * `shutter` - the time interval during which the shutter is open
+    pub fn with_motion(self, velocity: Vect3, angular: Vect3, shutter: (f32, f32)) -> Camera {
+        Camera {
+            velocity,
+            angular,
+            shutter,
+            ..self
+        }
+    }
+
+    /// Change the time interval during which the shutter is open.
+    ///
+    /// Rays are cast at random times in this interval, which causes motion
+    /// blur for moving objects (and, see `with_motion`, a moving camera).
+    pub fn with_shutter(self, shutter: (f32, f32)) -> Camera {
+        Camera { shutter, ..self }
+    }
+
+    /// Get the cameras of the left and right eye of a stereo pair.
+    ///
+    /// Both cameras are displaced sideways from this camera, by half the
+    /// interpupillary distance each, looking in parallel directions.
+    pub fn stereo_pair(&self, ipd: f32) -> (Camera, Camera) {
+        let (x, _, _) = &self.camera_cs;
+        let eye = |offset: Vect3| Camera {
+            origin: self.origin + offset,
+            corner: self.corner + offset,
+            ..*self
+        };
+        (eye(x * (-ipd / 2.0)), eye(x * (ipd / 2.0)))
+    }
+
+    /// Sample a single point for a regular polygon (or circular) aperture.
     fn sample_aperture(&self, sides: u32) -> Vect3 {
-        let mut rng = thread_rng();
+        let mut rng = sampler::rng();
+        let (x, y, _) = &self.camera_cs;
+        if sides < 3 {
+            let [u, v]: [f32; 2] = rng.sample(UnitDisc);
+            return self.radius * ((x * u) + (y * v));
+        }
         let angle = 2.0 * PI * (sides as f32).recip();
         // Genetare a random point on an isosceles triangle with angle
         // 2π / N between the legs. Then, rotate this triangle by 2πn / N,
@@ -88,7 +275,6 @@ impl Camera {
         let point = rand_point_in_triangle(angle);
         let s = ((segment as f32) * angle).sin();
         let c = ((segment as f32) * angle).cos();
-        let (x, y, _) = &self.camera_cs;
         // In order to keep the area equal to the corresponding perfectly
         // circular aperture, the radius must be scaled. The relative area
         // of the polygon is given by N/2π sin(2π/N), so the radius must
@@ -97,11 +283,384 @@ impl Camera {
         radius * ((x * (point.x() * c - point.y() * s)) + (y * (point.x() * s + point.y() * c)))
     }
 
+    /// Get the aspect ratio (width over height) of the viewport.
+    pub fn aspect_ratio(&self) -> f32 {
+        let (w, h) = &self.image_plane;
+        w.norm() / h.norm()
+    }
+
+    /// Get the distance from the camera origin to the (focused) image plane.
+    fn focus_distance(&self) -> f32 {
+        let (_, _, z) = &self.camera_cs;
+        (self.origin - self.corner).dot(*z)
+    }
+
+    /// Get the intrinsic (or calibration) matrix of the camera.
+    ///
+    /// This matrix maps points in camera coordinates, as given by the
+    /// `extrinsics`, to homogeneous pixel coordinates in an image of the
+    /// given size. Pixel coordinates have their origin in the top left
+    /// corner of the image, with `y` pointing downwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - image width, in pixels
+    /// * `height` - image height, in pixels
+    pub fn intrinsics(&self, width: usize, height: usize) -> Mat3 {
+        let (w, h) = &self.image_plane;
+        let (width, height) = (width as f32, height as f32);
+        let fx = width * self.focus_distance() / w.norm();
+        let fy = height * self.focus_distance() / h.norm();
+        Mat3([
+            [fx, 0.0, width / 2.0],
+            [0.0, fy, height / 2.0],
+            [0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Get the extrinsic matrix of the camera, as a rotation and translation.
+    ///
+    /// These map a point `p` in world coordinates to `rotation * p + translation`
+    /// in camera coordinates, following the common convention of the camera
+    /// looking along `+z` with `y` pointing downwards. Any camera motion is
+    /// ignored, i.e. this is the camera pose at time `0.0`.
+    pub fn extrinsics(&self) -> (Mat3, Vect3) {
+        let (x, y, z) = &self.camera_cs;
+        let rotation = Mat3::from_rows([*x, -y, -z]);
+        let translation = -(rotation * (self.origin - Point3::zero()));
+        (rotation, translation)
+    }
+
+    /// Project a point onto the image plane, getting its pixel coordinates.
+    ///
+    /// Pixel coordinates have their origin in the top left corner of an image
+    /// of the given size, with `y` pointing downwards. Points behind the camera
+    /// can not be projected.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - the point to project
+    /// * `width` - image width, in pixels
+    /// * `height` - image height, in pixels
+    pub fn project(&self, point: Point3, width: usize, height: usize) -> Option<(f32, f32)> {
+        let (w, h) = &self.image_plane;
+        let (_, _, z) = &self.camera_cs;
+        let offset = point - self.origin;
+        let depth = -offset.dot(*z);
+        if depth <= 0.0 {
+            return None;
+        }
+        // Scale the offset to the image plane, then find its viewport position.
+        let offset = offset * (self.focus_distance() / depth);
+        let position = (self.origin + offset) - self.corner;
+        let u = position.dot(*w) / w.dot(*w);
+        let v = position.dot(*h) / h.dot(*h);
+        Some((u * (width as f32), (1.0 - v) * (height as f32)))
+    }
+
+    /// Get the rectangle (in pixel coordinates) enclosing a surface.
+    ///
+    /// The corners of the bounding box of the surface are projected onto
+    /// the image plane, so the rectangle may be larger than the surface, and
+    /// may extend outside the image. If the surface is partly behind the
+    /// camera, the rectangle covers the whole image. If it is entirely
+    /// behind the camera, there is no rectangle.
+    ///
+    /// # Arguments
+    ///
+    /// * `surface` - the surface to enclose
+    /// * `width` - image width, in pixels
+    /// * `height` - image height, in pixels
+    pub fn screen_bounds(
+        &self,
+        surface: &dyn Surface,
+        width: usize,
+        height: usize,
+    ) -> Option<Rect> {
+        let bounds = surface.bounding_box();
+        let corners = (0..8).map(|corner| {
+            Point3(
+                if corner & 1 == 0 {
+                    bounds.min.x()
+                } else {
+                    bounds.max.x()
+                },
+                if corner & 2 == 0 {
+                    bounds.min.y()
+                } else {
+                    bounds.max.y()
+                },
+                if corner & 4 == 0 {
+                    bounds.min.z()
+                } else {
+                    bounds.max.z()
+                },
+            )
+        });
+        let projected: Vec<Option<(f32, f32)>> = corners
+            .map(|corner| self.project(corner, width, height))
+            .collect();
+        if projected.iter().all(Option::is_none) {
+            return None;
+        }
+        if projected.iter().any(Option::is_none) {
+            return Some(Rect {
+                min: (0.0, 0.0),
+                max: (width as f32, height as f32),
+            });
+        }
+        projected.into_iter().flatten().fold(None, |rect, (x, y)| {
+            Some(match rect {
+                None => Rect {
+                    min: (x, y),
+                    max: (x, y),
+                },
+                Some(Rect { min, max }) => Rect {
+                    min: (min.0.min(x), min.1.min(y)),
+                    max: (max.0.max(x), max.1.max(y)),
+                },
+            })
+        })
+    }
+
     /// Get a ray pointing through a specific viewport position.
+    ///
+    /// If the camera is moving, the ray is cast at a random time while the
+    /// shutter is open.
     pub fn ray(&self, u: f32, v: f32) -> Ray {
-        let (w, h) = &self.image_plane;
-        let offset = self.sample_aperture(5);
-        let direction = self.corner + (w * u) + (h * v) - self.origin;
-        Ray::new(self.origin + offset, direction - offset)
+        let (t0, t1) = self.shutter;
+        let time = if t0 < t1 {
+            sampler::rng().sample(Uniform::new(t0, t1))
+        } else {
+            t0
+        };
+        self.ray_at(u, v, time)
+    }
+
+    /// Get a ray pointing through a specific viewport position at a given time.
+    pub fn ray_at(&self, u: f32, v: f32, time: f32) -> Ray {
+        let (offset, direction) = match self.projection {
+            Projection::Perspective => {
+                let (w, h) = &self.image_plane;
+                let offset = self.sample_aperture(self.aperture_sides);
+                (offset, self.corner + (w * u) + (h * v) - self.origin)
+            }
+            Projection::Equirectangular => {
+                let (x, y, z) = &self.camera_cs;
+                let longitude = 2.0 * PI * u;
+                let latitude = PI * (v - 0.5);
+                let horizontal = (x * -longitude.sin()) + (z * longitude.cos());
+                let direction = (horizontal * latitude.cos()) + (y * latitude.sin());
+                (Vect3::zero(), direction)
+            }
+        };
+        let rotation = self.angular * time;
+        let origin = self.origin + (self.velocity * time) + rotate(offset, rotation);
+        Ray::new(origin, rotate(direction - offset, rotation)).at_time(time)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    fn camera() -> Camera {
+        Camera::new(
+            Point3::zero(),
+            Point3(0.0, 0.0, -1.0),
+            Vect3(0.0, 1.0, 0.0),
+            1.0,
+            f32::INFINITY,
+            (2.0, 2.0),
+        )
+    }
+
+    #[test]
+    fn test_with_fov() {
+        let origin = Point3(-2.0, 2.0, 1.0);
+        let target = Point3(0.0, 0.0, -1.0);
+        let vertical = Vect3(0.0, 1.0, 0.0);
+        let camera = Camera::with_fov(origin, target, vertical, 40.0, 16.0, 1.5);
+
+        let viewport = (3.0_f32, 2.0_f32);
+        let diagonal = (viewport.0.powi(2) + viewport.1.powi(2)).sqrt();
+        let focal_length = (diagonal / 2.0) / (40.0_f32.to_radians() / 2.0).tan();
+        let expected = Camera::new(origin, target, vertical, focal_length, 16.0, viewport);
+        assert_abs_diff_eq!(camera.corner, expected.corner);
+        assert_abs_diff_eq!(camera.image_plane.0, expected.image_plane.0);
+        assert_abs_diff_eq!(camera.image_plane.1, expected.image_plane.1);
+        assert_abs_diff_eq!(camera.aspect_ratio(), 1.5, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_panoramic() {
+        let camera = Camera::panoramic(Point3(1.0, 2.0, 3.0));
+        assert_abs_diff_eq!(camera.aspect_ratio(), 2.0);
+
+        let forward = camera.ray(0.5, 0.5);
+        assert_eq!(forward.origin(), Point3(1.0, 2.0, 3.0));
+        assert_abs_diff_eq!(forward.direction(), Vect3(0.0, 0.0, -1.0), epsilon = 1e-6);
+        let right = camera.ray(0.75, 0.5).direction();
+        assert_abs_diff_eq!(right, Vect3(1.0, 0.0, 0.0), epsilon = 1e-6);
+        let up = camera.ray(0.3, 1.0).direction();
+        assert_abs_diff_eq!(up, Vect3(0.0, 1.0, 0.0), epsilon = 1e-6);
+
+        for v in [0.1, 0.5, 0.8] {
+            let first = camera.ray(0.0, v).direction();
+            let last = camera.ray(1.0, v).direction();
+            assert_abs_diff_eq!(first, last, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_linear_motion() {
+        let velocity = Vect3(1.0, 2.0, 0.0);
+        let camera = camera().with_motion(velocity, Vect3::zero(), (0.25, 0.75));
+
+        let start = camera.ray_at(0.5, 0.5, 0.25);
+        let end = camera.ray_at(0.5, 0.5, 0.75);
+        assert_abs_diff_eq!(
+            end.origin() - start.origin(),
+            velocity * 0.5,
+            epsilon = 1e-5
+        );
+        assert_abs_diff_eq!(end.direction(), start.direction(), epsilon = 1e-5);
+        for _ in 0..10 {
+            let offset = camera.ray(0.5, 0.5).origin() - Point3::zero();
+            assert!((0.25..0.75).contains(&offset.x()));
+            assert_abs_diff_eq!(offset.y(), 2.0 * offset.x(), epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_angular_motion() {
+        let angular = Vect3(0.0, std::f32::consts::FRAC_PI_2, 0.0);
+        let camera = camera().with_motion(Vect3::zero(), angular, (0.0, 1.0));
+
+        let start = camera.ray_at(0.5, 0.5, 0.0);
+        let end = camera.ray_at(0.5, 0.5, 1.0);
+        assert_abs_diff_eq!(
+            start.direction().normalize(),
+            Vect3(0.0, 0.0, -1.0),
+            epsilon = 1e-5
+        );
+        assert_abs_diff_eq!(
+            end.direction().normalize(),
+            Vect3(-1.0, 0.0, 0.0),
+            epsilon = 1e-5
+        );
+        assert_abs_diff_eq!(end.origin(), start.origin(), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_shutter_time() {
+        let camera = camera().with_shutter((0.25, 0.75));
+
+        assert_eq!(camera.ray_at(0.5, 0.5, 0.5).time(), 0.5);
+        for _ in 0..10 {
+            assert!((0.25..0.75).contains(&camera.ray(0.5, 0.5).time()));
+        }
+        assert_eq!(self::camera().ray(0.5, 0.5).time(), 0.0);
+    }
+
+    #[test]
+    fn test_projection_matrices() {
+        let camera = Camera::new(
+            Point3(1.0, 2.0, 3.0),
+            Point3(0.0, 0.0, -1.0),
+            Vect3(0.0, 1.0, 0.0),
+            1.5,
+            f32::INFINITY,
+            (3.0, 2.0),
+        );
+        let (width, height) = (300, 200);
+        let intrinsics = camera.intrinsics(width, height);
+        let (rotation, translation) = camera.extrinsics();
+
+        for point in [
+            Point3(0.0, 0.0, -1.0),
+            Point3(0.5, -0.25, 0.0),
+            Point3(-2.0, 1.0, -4.0),
+            Point3(1.0, 3.0, -2.0),
+        ] {
+            let pixel = intrinsics * (rotation * (point - Point3::zero()) + translation);
+            let (x, y) = camera.project(point, width, height).unwrap();
+            assert_abs_diff_eq!(x, pixel.x() / pixel.z(), epsilon = 1e-3);
+            assert_abs_diff_eq!(y, pixel.y() / pixel.z(), epsilon = 1e-3);
+        }
+        let (x, y) = camera
+            .project(Point3(0.0, 0.0, -1.0), width, height)
+            .unwrap();
+        assert_abs_diff_eq!(x, 150.0, epsilon = 1e-3);
+        assert_abs_diff_eq!(y, 100.0, epsilon = 1e-3);
+        assert_eq!(camera.project(Point3(2.0, 4.0, 7.0), width, height), None);
+    }
+
+    #[test]
+    fn test_project_ray() {
+        let camera = camera();
+        let ray = camera.ray_at(0.25, 0.75, 0.0);
+        let (x, y) = camera.project(ray.at(5.0), 40, 40).unwrap();
+        assert_abs_diff_eq!(x, 10.0, epsilon = 1e-3);
+        assert_abs_diff_eq!(y, 10.0, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_screen_bounds() {
+        let camera = camera();
+        let bounds = |z: f32| {
+            let sphere = crate::surfaces::Sphere {
+                center: Point3(0.0, 0.0, z),
+                radius: 0.5,
+            };
+            camera.screen_bounds(&sphere, 64, 64)
+        };
+
+        let near = bounds(-2.0).unwrap();
+        let far = bounds(-4.0).unwrap();
+        for rect in [near, far] {
+            let (x, y) = rect.center();
+            assert_abs_diff_eq!(x, 32.0, epsilon = 1e-3);
+            assert_abs_diff_eq!(y, 32.0, epsilon = 1e-3);
+        }
+        assert!(far.width() < near.width());
+        assert!(far.height() < near.height());
+        assert_eq!(bounds(4.0), None);
+        assert_eq!(
+            bounds(0.0),
+            Some(Rect {
+                min: (0.0, 0.0),
+                max: (64.0, 64.0)
+            })
+        );
+    }
+
+    #[test]
+    fn test_aperture_shapes() {
+        let camera = Camera::new(
+            Point3::zero(),
+            Point3(0.0, 0.0, -1.0),
+            Vect3(0.0, 1.0, 0.0),
+            1.0,
+            1.0,
+            (2.0, 2.0),
+        );
+        let radius = 0.5;
+        let mean_square = |sides: u32| {
+            let samples: Vec<Vect3> = (0..10000).map(|_| camera.sample_aperture(sides)).collect();
+            let norms: Vec<f32> = samples.iter().map(|sample| sample.norm()).collect();
+            let max = norms.iter().cloned().fold(0.0, f32::max);
+            let mean = norms.iter().map(|norm| norm * norm).sum::<f32>() / (norms.len() as f32);
+            (max, mean)
+        };
+
+        let (circle_max, circle_mean) = mean_square(0);
+        let (polygon_max, polygon_mean) = mean_square(64);
+        assert!(circle_max <= radius);
+        assert!(polygon_max <= radius * 1.01);
+        // The mean square distance from the center of a disk is `r² / 2`.
+        assert!((circle_mean - radius * radius / 2.0).abs() < 0.01);
+        assert!((polygon_mean - circle_mean).abs() < 0.01);
     }
 }
@@ -0,0 +1,128 @@
+use crate::image::Pixel;
+use std::str::FromStr;
+
+/// A linear RGB color space, defined by its primaries and white point.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// Linear sRGB (Rec. 709 primaries, D65 white point).
+    #[default]
+    Srgb,
+    /// ACEScg (AP1 primaries, D60 white point).
+    AcesCg,
+}
+
+impl FromStr for ColorSpace {
+    type Err = String;
+
+    /// Parse a color space from its (case-insensitive) name, i.e. one of
+    /// `srgb` or `aces-cg`.
+    fn from_str(name: &str) -> Result<ColorSpace, String> {
+        match name.to_lowercase().as_str() {
+            "srgb" => Ok(ColorSpace::Srgb),
+            "aces-cg" => Ok(ColorSpace::AcesCg),
+            _ => Err(format!("Unknown color space: {}", name)),
+        }
+    }
+}
+
+impl ColorSpace {
+    /// Get the matrix converting colors in this space into linear sRGB.
+    fn srgb_matrix(self) -> [[f32; 3]; 3] {
+        match self {
+            ColorSpace::Srgb => [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            // Includes Bradford adaptation from D60 to D65.
+            ColorSpace::AcesCg => [
+                [1.70505, -0.62179, -0.08326],
+                [-0.13026, 1.14080, -0.01055],
+                [-0.02400, -0.12897, 1.15297],
+            ],
+        }
+    }
+
+    /// Get the matrix converting colors in linear sRGB into this space.
+    fn inverse_srgb_matrix(self) -> [[f32; 3]; 3] {
+        match self {
+            ColorSpace::Srgb => [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            // Includes Bradford adaptation from D65 to D60.
+            ColorSpace::AcesCg => [
+                [0.61310, 0.33952, 0.04737],
+                [0.07019, 0.91636, 0.01345],
+                [0.02062, 0.10957, 0.86981],
+            ],
+        }
+    }
+}
+
+/// Multiply a pixel (as a column vector) by a matrix.
+fn transform(matrix: [[f32; 3]; 3], pixel: Pixel) -> Pixel {
+    let [r, g, b] =
+        matrix.map(|row| row[0] * pixel.red() + row[1] * pixel.green() + row[2] * pixel.blue());
+    Pixel(r, g, b)
+}
+
+/// Convert a pixel from one color space to another.
+///
+/// Colors outside the gamut of the target space are clamped, so that no
+/// channel is negative.
+///
+/// # Arguments
+///
+/// * `pixel` - the pixel to convert
+/// * `from` - the color space of the pixel
+/// * `to` - the color space to convert into
+pub fn convert(pixel: Pixel, from: ColorSpace, to: ColorSpace) -> Pixel {
+    if from == to {
+        return pixel;
+    }
+    let converted = transform(
+        to.inverse_srgb_matrix(),
+        transform(from.srgb_matrix(), pixel),
+    );
+    Pixel(
+        converted.red().max(0.0),
+        converted.green().max(0.0),
+        converted.blue().max(0.0),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("srgb".parse(), Ok(ColorSpace::Srgb));
+        assert_eq!("ACES-cg".parse(), Ok(ColorSpace::AcesCg));
+        assert!("rec2020".parse::<ColorSpace>().is_err());
+    }
+
+    #[test]
+    fn test_identity() {
+        let pixel = Pixel(0.25, -0.5, 2.0);
+        assert_eq!(convert(pixel, ColorSpace::Srgb, ColorSpace::Srgb), pixel);
+        assert_eq!(
+            convert(pixel, ColorSpace::AcesCg, ColorSpace::AcesCg),
+            pixel
+        );
+    }
+
+    #[test]
+    fn test_acescg_green_to_srgb() {
+        let green = convert(Pixel(0.0, 1.0, 0.0), ColorSpace::AcesCg, ColorSpace::Srgb);
+        assert_eq!(green.red(), 0.0);
+        assert!((green.green() - 1.1408).abs() < 1e-4);
+        assert_eq!(green.blue(), 0.0);
+        assert_eq!(green.to_rgb8(1.0), [0, 255, 0]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let pixel = Pixel(0.25, 0.5, 0.75);
+        let wide = convert(pixel, ColorSpace::Srgb, ColorSpace::AcesCg);
+        let back = convert(wide, ColorSpace::AcesCg, ColorSpace::Srgb);
+        assert!((back - pixel).red().abs() < 1e-3);
+        assert!((back - pixel).green().abs() < 1e-3);
+        assert!((back - pixel).blue().abs() < 1e-3);
+    }
+}
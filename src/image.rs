@@ -1,7 +1,14 @@
+use crate::colorspace::{self, ColorSpace};
 use auto_ops::*;
+use rayon::prelude::*;
 use std::ops::{Index, IndexMut};
 use std::slice::{ChunksExact, ChunksExactMut};
 
+/// Clamp a linear channel value into `[0, 1]` and apply gamma correction.
+pub(crate) fn gamma_encode(value: f32, gamma: f32) -> f32 {
+    value.clamp(0.0, 1.0).powf(gamma.recip())
+}
+
 /// Pixels are represented using three floating-point color channels,
 /// with range from `0.0` to `1.0`. There is no alpha channel.
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
@@ -22,6 +29,36 @@ impl Pixel {
     pub fn blue(&self) -> f32 {
         self.2
     }
+
+    /// The relative luminance of the pixel, using Rec. 709 coefficients.
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.red() + 0.7152 * self.green() + 0.0722 * self.blue()
+    }
+
+    /// Adjust the saturation of the pixel.
+    ///
+    /// The pixel is interpolated between a gray pixel of the same luminance
+    /// (at `0.0`) and the original pixel (at `1.0`). Amounts larger than `1.0`
+    /// increase the saturation.
+    pub fn saturate(self, amount: f32) -> Pixel {
+        let luminance = self.luminance();
+        let gray = Pixel(luminance, luminance, luminance);
+        gray + (self - gray) * amount
+    }
+
+    /// Convert the pixel into clamped, gamma-corrected 8-bit channels.
+    pub fn to_rgb8(self, gamma: f32) -> [u8; 3] {
+        [self.0, self.1, self.2].map(|value| (gamma_encode(value, gamma) * 255.0).round() as u8)
+    }
+
+    /// Reorder the channels of the pixel.
+    ///
+    /// Each element of `order` is the index of the channel (`0` for red,
+    /// `1` for green and `2` for blue) to use for that output channel.
+    pub fn swap_channels(self, order: [usize; 3]) -> Pixel {
+        let channels = [self.0, self.1, self.2];
+        Pixel(channels[order[0]], channels[order[1]], channels[order[2]])
+    }
 }
 
 impl_op_ex!(+= |a: &mut Pixel, b: &Pixel| { *a = *a + b; });
@@ -39,9 +76,55 @@ impl_op_ex_commutative!(*|a: &Pixel, b: &f32| -> Pixel { Pixel(a.0 * b, a.1 * b,
 impl_op_ex!(/= |a: &mut Pixel, b: &f32| { *a = *a / b; });
 impl_op_ex!(/|a: &Pixel, b: &f32| -> Pixel { Pixel(a.0 / b, a.1 / b, a.2 / b) });
 
+/// A mode used to blend one image onto another.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlendMode {
+    /// Sum the pixels of both images.
+    Add,
+    /// Multiply the pixels of both images channel-wise.
+    Multiply,
+    /// Invert, multiply and invert again; the opposite of `Multiply`.
+    Screen,
+    /// Place the other image over this one with a given opacity.
+    Over(f32),
+}
+
+impl BlendMode {
+    /// Blend a single pair of pixels.
+    fn apply(&self, bottom: &Pixel, top: &Pixel) -> Pixel {
+        let white = Pixel(1.0, 1.0, 1.0);
+        match *self {
+            BlendMode::Add => bottom + top,
+            BlendMode::Multiply => bottom * top,
+            BlendMode::Screen => white - (white - bottom) * (white - top),
+            BlendMode::Over(alpha) => (bottom * (1.0 - alpha)) + (top * alpha),
+        }
+    }
+}
+
+/// A color scheme used to combine a stereo pair into an anaglyph image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnaglyphMode {
+    /// Left eye in the red channel, right eye in the green and blue channels.
+    RedCyan,
+    /// Left eye in the green channel, right eye in the red and blue channels.
+    GreenMagenta,
+}
+
+impl AnaglyphMode {
+    /// Combine the luminance of a single pair of pixels.
+    fn combine(&self, left: &Pixel, right: &Pixel) -> Pixel {
+        let (left, right) = (left.luminance(), right.luminance());
+        match *self {
+            AnaglyphMode::RedCyan => Pixel(left, right, right),
+            AnaglyphMode::GreenMagenta => Pixel(right, left, right),
+        }
+    }
+}
+
 /// An image is a two-dimensional matrix of pixels, with its origin
 /// in the top left corner.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Image {
     width: usize,
     height: usize,
@@ -73,6 +156,44 @@ impl Image {
         }
     }
 
+    /// Constructs an image with a given size, computing each pixel from
+    /// its coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - the width of the image (number of columns)
+    /// * `height` - the height of the image (number of rows)
+    /// * `f` - function computing the pixel at a column and row
+    pub fn from_fn(width: usize, height: usize, f: impl Fn(usize, usize) -> Pixel) -> Image {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Returns the pixel at a column and row, or `None` if out of range.
+    pub fn get(&self, x: usize, y: usize) -> Option<&Pixel> {
+        if x < self.width && y < self.height {
+            self.pixels.get(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the mutable pixel at a column and row, or `None` if out of range.
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut Pixel> {
+        if x < self.width && y < self.height {
+            self.pixels.get_mut(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
     /// Returns the width of the image.
     pub fn width(&self) -> usize {
         self.width
@@ -111,6 +232,201 @@ impl Image {
     pub fn iter_mut(&mut self) -> ChunksExactMut<'_, Pixel> {
         self.pixels.chunks_exact_mut(self.width)
     }
+
+    /// Returns a parallel iterator that allows modifying each row.
+    pub fn par_iter_mut(&mut self) -> rayon::slice::ChunksExactMut<'_, Pixel> {
+        self.pixels.par_chunks_exact_mut(self.width)
+    }
+
+    /// Returns a copy of the image quantized as if it was saved to file.
+    ///
+    /// Each channel is clamped, gamma-corrected and rounded to `bits` bits,
+    /// then converted back to linear space. This makes previews show the
+    /// same banding as the saved image. The number of bits must be in
+    /// `1..=16`, or this function will panic.
+    ///
+    /// # Arguments
+    ///
+    /// * `gamma` - gamma correction applied when saving
+    /// * `bits` - bits per channel when saving
+    pub fn quantize_preview(&self, gamma: f32, bits: u32) -> Image {
+        assert!(
+            (1..=16).contains(&bits),
+            "Unsupported bits per channel: {}",
+            bits
+        );
+        let levels = ((1_u32 << bits) - 1) as f32;
+        let quantize = |value: f32| {
+            let encoded = (gamma_encode(value, gamma) * levels).round() / levels;
+            encoded.powf(gamma)
+        };
+        let pixels = self
+            .pixels
+            .iter()
+            .map(|pixel| Pixel(quantize(pixel.0), quantize(pixel.1), quantize(pixel.2)))
+            .collect();
+        Image {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+
+    /// Returns a copy of the image, downsampled by averaging blocks of pixels.
+    ///
+    /// Each `factor` by `factor` block of pixels is averaged (i.e. box
+    /// filtered) into a single pixel, which anti-aliases an image rendered
+    /// at a higher resolution. Both dimensions of the image must be
+    /// multiples of `factor`, or this function will panic.
+    ///
+    /// # Arguments
+    ///
+    /// * `factor` - the number of pixels to average along each axis
+    pub fn downsample(&self, factor: usize) -> Image {
+        assert!(
+            factor > 0 && self.width.is_multiple_of(factor) && self.height.is_multiple_of(factor),
+            "Cannot downsample a {}x{} image by a factor of {}",
+            self.width,
+            self.height,
+            factor
+        );
+        let scale = ((factor * factor) as f32).recip();
+        Image::from_fn(self.width / factor, self.height / factor, |x, y| {
+            (0..factor)
+                .flat_map(|dy| (0..factor).map(move |dx| (dx, dy)))
+                .map(|(dx, dy)| self.pixels[(y * factor + dy) * self.width + x * factor + dx])
+                .fold(Pixel::default(), |acc, pixel| acc + pixel)
+                * scale
+        })
+    }
+
+    /// Converts the image from one color space to another, pixel by pixel.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - the (working) color space the image was rendered in
+    /// * `to` - the (output) color space to convert into
+    pub fn convert_color_space(&mut self, from: ColorSpace, to: ColorSpace) {
+        for pixel in self.pixels.iter_mut() {
+            *pixel = colorspace::convert(*pixel, from, to);
+        }
+    }
+
+    /// Flips the image upside down, in place.
+    pub fn flip_vertical(&mut self) {
+        let width = self.width;
+        for y in 0..self.height / 2 {
+            let (top, bottom) = self.pixels.split_at_mut((self.height - 1 - y) * width);
+            top[y * width..(y + 1) * width].swap_with_slice(&mut bottom[..width]);
+        }
+    }
+
+    /// Mirrors the image left to right, in place.
+    pub fn flip_horizontal(&mut self) {
+        for row in self.iter_mut() {
+            row.reverse();
+        }
+    }
+
+    /// Blends another image onto this one, pixel by pixel.
+    ///
+    /// Blending happens in linear space, i.e. before any gamma correction.
+    /// Both images must have the same dimensions, or this function will panic.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - the image to blend onto this one
+    /// * `mode` - the blend mode to use
+    pub fn blend(&mut self, other: &Image, mode: BlendMode) {
+        assert!(
+            self.width == other.width && self.height == other.height,
+            "Cannot blend a {}x{} image onto a {}x{} image",
+            other.width,
+            other.height,
+            self.width,
+            self.height
+        );
+        for (pixel, top) in self.pixels.iter_mut().zip(other.pixels.iter()) {
+            *pixel = mode.apply(pixel, top);
+        }
+    }
+
+    /// Combines the images of a stereo pair into a single anaglyph image.
+    ///
+    /// Both images must have the same dimensions, or this function will panic.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - the image seen by the left eye
+    /// * `right` - the image seen by the right eye
+    /// * `mode` - the color scheme used to combine the images
+    pub fn anaglyph(left: &Image, right: &Image, mode: AnaglyphMode) -> Image {
+        assert!(
+            left.width == right.width && left.height == right.height,
+            "Cannot combine a {}x{} image with a {}x{} image",
+            left.width,
+            left.height,
+            right.width,
+            right.height
+        );
+        let pixels = left
+            .pixels
+            .iter()
+            .zip(right.pixels.iter())
+            .map(|(left, right)| mode.combine(left, right))
+            .collect();
+        Image {
+            width: left.width,
+            height: left.height,
+            pixels,
+        }
+    }
+
+    /// Returns a copy of the image with a fake depth-of-field effect.
+    ///
+    /// Each pixel is blurred by averaging the pixels in a disk around it,
+    /// whose radius (in pixels) is `strength` times the difference between
+    /// the depth of the pixel and `focus_distance`. This approximates the
+    /// defocus of a real lens, without tracing any additional rays.
+    ///
+    /// Both images must have the same dimensions, or this function will panic.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - the depth of each pixel, in its red channel
+    /// * `focus_distance` - the depth at which the image is sharp
+    /// * `strength` - blur radius per unit of depth from the focus distance
+    pub fn fake_dof(&self, depth: &Image, focus_distance: f32, strength: f32) -> Image {
+        assert!(
+            self.width == depth.width && self.height == depth.height,
+            "Depth buffer size {}x{} does not match image size {}x{}",
+            depth.width,
+            depth.height,
+            self.width,
+            self.height
+        );
+        let mut image = Image::new(self.width, self.height);
+        for (index, pixel) in image.pixels.iter_mut().enumerate() {
+            let (x, y) = ((index % self.width) as isize, (index / self.width) as isize);
+            let radius = strength * (depth.pixels[index].red() - focus_distance).abs();
+            let reach = radius.floor() as isize;
+            let mut acc = Pixel::default();
+            let mut count = 0;
+            for dy in -reach..=reach {
+                for dx in -reach..=reach {
+                    let (nx, ny) = (x + dx, y + dy);
+                    let inside = (0..self.width as isize).contains(&nx)
+                        && (0..self.height as isize).contains(&ny);
+                    if inside && ((dx * dx + dy * dy) as f32) <= radius * radius {
+                        acc += self.pixels[(ny as usize) * self.width + (nx as usize)];
+                        count += 1;
+                    }
+                }
+            }
+            *pixel = acc / (count.max(1) as f32);
+        }
+        image
+    }
 }
 
 impl Index<usize> for Image {
@@ -156,6 +472,59 @@ mod test {
         assert_eq!(Pixel::default(), expected);
     }
 
+    #[test]
+    fn test_pixel_ops() {
+        let pixel = Pixel(0.5, 1.0, 2.0);
+        let other = Pixel(2.0, 0.5, 0.25);
+
+        assert_eq!(pixel + other, Pixel(2.5, 1.5, 2.25));
+        assert_eq!(pixel - other, Pixel(-1.5, 0.5, 1.75));
+        assert_eq!(pixel * other, Pixel(1.0, 0.5, 0.5));
+        assert_eq!(pixel * 2.0, Pixel(1.0, 2.0, 4.0));
+        assert_eq!(2.0 * pixel, Pixel(1.0, 2.0, 4.0));
+        assert_eq!(pixel / 2.0, Pixel(0.25, 0.5, 1.0));
+    }
+
+    #[test]
+    fn test_pixel_assign_ops() {
+        let mut pixel = Pixel(0.5, 1.0, 2.0);
+
+        pixel += Pixel(0.5, 0.0, -1.0);
+        assert_eq!(pixel, Pixel(1.0, 1.0, 1.0));
+        pixel -= Pixel(0.5, 0.0, 0.0);
+        assert_eq!(pixel, Pixel(0.5, 1.0, 1.0));
+        pixel *= Pixel(2.0, 3.0, 0.0);
+        assert_eq!(pixel, Pixel(1.0, 3.0, 0.0));
+        pixel *= 2.0;
+        assert_eq!(pixel, Pixel(2.0, 6.0, 0.0));
+        pixel /= 4.0;
+        assert_eq!(pixel, Pixel(0.5, 1.5, 0.0));
+    }
+
+    #[test]
+    fn test_pixel_saturate() {
+        let pixel = Pixel(0.8, 0.4, 0.2);
+        let gray = pixel.saturate(0.0);
+        assert_eq!(gray.red(), gray.green());
+        assert_eq!(gray.green(), gray.blue());
+        assert_eq!(gray.red(), pixel.luminance());
+        let same = pixel.saturate(1.0) - pixel;
+        assert!(
+            same.red()
+                .abs()
+                .max(same.green().abs())
+                .max(same.blue().abs())
+                < 1e-6
+        );
+    }
+
+    #[test]
+    fn test_pixel_swap_channels() {
+        let pixel = Pixel(0.1, 0.2, 0.3);
+        assert_eq!(pixel.swap_channels([2, 1, 0]), Pixel(0.3, 0.2, 0.1));
+        assert_eq!(pixel.swap_channels([0, 1, 2]), pixel);
+    }
+
     #[test]
     fn test_image_size_accessors() {
         let image = Image::new(32, 8);
@@ -174,6 +543,85 @@ mod test {
         assert_eq!(image[1], vec![gray(0.2), gray(0.3)]);
     }
 
+    #[test]
+    fn test_image_from_fn() {
+        let image = Image::from_fn(4, 2, |x, y| Pixel(x as f32 / 3.0, y as f32, 0.0));
+        assert_eq!(image.width(), 4);
+        assert_eq!(image.height(), 2);
+        assert_eq!(image[0][0], Pixel(0.0, 0.0, 0.0));
+        assert_eq!(image[0][3], Pixel(1.0, 0.0, 0.0));
+        assert_eq!(image[1][0], Pixel(0.0, 1.0, 0.0));
+        assert!(image
+            .iter()
+            .all(|row| row.windows(2).all(|pair| pair[0].red() < pair[1].red())));
+    }
+
+    #[test]
+    fn test_image_get() {
+        let mut image = Image::new(3, 2);
+        *image.get_mut(2, 1).unwrap() = Pixel(1.0, 0.5, 0.25);
+        assert_eq!(image.get(2, 1), Some(&Pixel(1.0, 0.5, 0.25)));
+        assert_eq!(image[1][2], Pixel(1.0, 0.5, 0.25));
+        assert_eq!(image.get(0, 0), Some(&Pixel::default()));
+        assert_eq!(image.get(3, 0), None);
+        assert_eq!(image.get(0, 2), None);
+        assert!(image.get_mut(3, 1).is_none());
+    }
+
+    #[test]
+    fn test_image_flip() {
+        let gray = |v: f32| Pixel(v, v, v);
+        let original = Image::from_fn(2, 2, |x, y| gray((2 * y + x) as f32 / 10.0));
+
+        let mut image = original.clone();
+        image.flip_vertical();
+        assert_eq!(image[0], vec![gray(0.2), gray(0.3)]);
+        assert_eq!(image[1], vec![gray(0.0), gray(0.1)]);
+        image.flip_vertical();
+        assert_eq!(image, original);
+
+        let mut image = original.clone();
+        image.flip_horizontal();
+        assert_eq!(image[0], vec![gray(0.1), gray(0.0)]);
+        assert_eq!(image[1], vec![gray(0.3), gray(0.2)]);
+        image.flip_horizontal();
+        assert_eq!(image, original);
+
+        let mut odd = Image::from_fn(1, 3, |_, y| gray(y as f32));
+        odd.flip_vertical();
+        assert_eq!(odd[1], vec![gray(1.0)]);
+        assert_eq!(odd[2], vec![gray(0.0)]);
+    }
+
+    #[test]
+    fn test_image_downsample() {
+        let color = Pixel(0.25, 0.5, 0.75);
+        let uniform = Image::from_fn(4, 6, |_, _| color).downsample(2);
+        assert_eq!(uniform.width(), 2);
+        assert_eq!(uniform.height(), 3);
+        assert!(uniform.iter().flatten().all(|pixel| *pixel == color));
+
+        let checkerboard = Image::from_fn(4, 4, |x, y| {
+            if (x + y) % 2 == 0 {
+                Pixel(1.0, 1.0, 1.0)
+            } else {
+                Pixel::default()
+            }
+        });
+        let gray = checkerboard.downsample(2);
+        assert!(gray
+            .iter()
+            .flatten()
+            .all(|pixel| *pixel == Pixel(0.5, 0.5, 0.5)));
+        assert_eq!(checkerboard.downsample(1), checkerboard);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_image_downsample_uneven() {
+        Image::new(5, 4).downsample(2);
+    }
+
     #[test]
     fn test_image_iter() {
         let image = Image::new(32, 8);
@@ -181,6 +629,42 @@ mod test {
         assert!(image.iter().all(|row| row.len() == image.width()));
     }
 
+    #[test]
+    fn test_image_blend_add() {
+        let mut image = Image::new(2, 2);
+        let mut other = Image::new(2, 2);
+        image.pixels.fill(Pixel(0.25, 0.5, 0.0));
+        other.pixels.fill(Pixel(0.5, 0.25, 1.0));
+        image.blend(&other, BlendMode::Add);
+        assert!(image.pixels.iter().all(|p| *p == Pixel(0.75, 0.75, 1.0)));
+    }
+
+    #[test]
+    fn test_image_blend_multiply() {
+        let mut image = Image::new(2, 2);
+        let mut other = Image::new(2, 2);
+        image.pixels.fill(Pixel(0.5, 1.0, 0.0));
+        other.pixels.fill(Pixel(0.5, 0.25, 1.0));
+        image.blend(&other, BlendMode::Multiply);
+        assert!(image.pixels.iter().all(|p| *p == Pixel(0.25, 0.25, 0.0)));
+    }
+
+    #[test]
+    fn test_image_blend_screen_black() {
+        let mut image = Image::new(2, 2);
+        let other = Image::new(2, 2);
+        image.pixels.fill(Pixel(0.25, 0.5, 0.75));
+        image.blend(&other, BlendMode::Screen);
+        assert!(image.pixels.iter().all(|p| *p == Pixel(0.25, 0.5, 0.75)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_image_blend_size_mismatch() {
+        let mut image = Image::new(2, 2);
+        image.blend(&Image::new(2, 1), BlendMode::Add);
+    }
+
     #[test]
     fn test_image_into_iter() {
         let image = Image::new(32, 8);
@@ -190,4 +674,64 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_quantize_preview_one_bit() {
+        let mut image = Image::new(2, 1);
+        image[0][0] = Pixel(0.2, 0.6, 1.5);
+        image[0][1] = Pixel(-0.3, 0.3, 0.1);
+
+        let linear = image.quantize_preview(1.0, 1);
+        assert_eq!(linear[0][0], Pixel(0.0, 1.0, 1.0));
+        assert_eq!(linear[0][1], Pixel(0.0, 0.0, 0.0));
+        let corrected = image.quantize_preview(2.2, 1);
+        assert_eq!(corrected[0][0], Pixel(0.0, 1.0, 1.0));
+        assert_eq!(corrected[0][1], Pixel(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_quantize_preview_no_bits() {
+        Image::new(2, 1).quantize_preview(2.2, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_quantize_preview_too_many_bits() {
+        Image::new(2, 1).quantize_preview(2.2, 32);
+    }
+
+    #[test]
+    fn test_anaglyph() {
+        let mut left = Image::new(2, 1);
+        let mut right = Image::new(2, 1);
+        left.pixels.fill(Pixel(1.0, 0.0, 0.0));
+        right.pixels.fill(Pixel(0.0, 0.0, 1.0));
+
+        let red_cyan = Image::anaglyph(&left, &right, AnaglyphMode::RedCyan);
+        assert_eq!(red_cyan[0][0], Pixel(0.2126, 0.0722, 0.0722));
+        let green_magenta = Image::anaglyph(&left, &right, AnaglyphMode::GreenMagenta);
+        assert_eq!(green_magenta[0][1], Pixel(0.0722, 0.2126, 0.0722));
+    }
+
+    #[test]
+    fn test_fake_dof() {
+        // A vertical stripe pattern, in focus on the left and far away on the right.
+        let mut image = Image::new(8, 4);
+        let mut depth = Image::new(8, 4);
+        for (index, pixel) in image.pixels.iter_mut().enumerate() {
+            let value = ((index % 8) % 2) as f32;
+            *pixel = Pixel(value, value, value);
+            let distance = if index % 8 < 4 { 2.0 } else { 10.0 };
+            depth.pixels[index] = Pixel(distance, distance, distance);
+        }
+
+        let blurred = image.fake_dof(&depth, 2.0, 0.25);
+        for y in 0..4 {
+            assert_eq!(blurred[y][..4], image[y][..4]);
+            for x in 5..7 {
+                assert!(blurred[y][x].red() > 0.0 && blurred[y][x].red() < 1.0);
+            }
+        }
+    }
 }
@@ -1,149 +1,143 @@
-use rand::{thread_rng, Rng};
+use rand::Rng;
 use rand_distr::Uniform;
-use std::io;
+use rayon::prelude::*;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::{error, fmt, fs, io, iter};
 
 mod camera;
+mod colorspace;
 mod image;
 mod materials;
+mod sampler;
 mod scene;
 mod surfaces;
+mod tonemap;
 mod types;
 
-use camera::Camera;
-pub use image::Image;
-use materials::{Color, Dielectric, Hemispherical, Lambertian, Material, Metal};
-use scene::{Object, Scene};
-use surfaces::Sphere;
-use types::{Point3, Vect3};
+pub use camera::{Camera, Rect};
+pub use colorspace::ColorSpace;
+pub use image::{AnaglyphMode, BlendMode, Image};
+pub use materials::{
+    Checker, Checkerboard, Color, Dielectric, DiffuseLight, Hemispherical, Isotropic, Lambertian,
+    Lobe, Material, Metal, Mix, OrenNayar, Phong, SolidColor, Texture,
+};
+pub use scene::{
+    Background, BvhBuildStrategy, Component, Object, PointLight, Scene, SceneBuilder,
+    SceneDescription,
+};
+pub use surfaces::{
+    load_obj, parse_obj, Aabb, ConstantMedium, Cylinder, Disk, Ellipsoid, Heightfield,
+    Intersection, MeshError, MovingSphere, Sphere, Surface, Transformed, Triangle,
+};
+pub use tonemap::ToneMap;
+pub use types::{Mat3, Mat4, Onb, Point3, Vect3};
 
 fn get_small_scene(aspect_ratio: f32) -> (Camera, Scene) {
-    // Viewport size.
-    let viewport = (2.0 * aspect_ratio, 2.0_f32);
-
-    // The angle of view can be determined using the diagonal image plane dimension `d`
-    // and the focal length `f`, using the relation `aov = 2 * arctan(d / 2f)`. Working
-    // backwards, we can calculate `f` as `f = d / (2 * tan(aov / 2))`.
+    // Camera definition
     // Note: Since we use diagonal field-of-view, exact angles will differ compared to
     // the book (depending on aspect ratio).
-    let angle_of_view = 40.0_f32.to_radians();
-    let diagonal = (viewport.0.powi(2) + viewport.1.powi(2)).sqrt();
-    let focal_length = (diagonal / 2.0) / (angle_of_view / 2.0).tan();
-    let aperture = 16.0;
-
-    // Camera definition
     let origin = Point3(-2.0, 2.0, 1.0);
     let target = Point3(0.0, 0.0, -1.0);
     let vertical = Vect3(0.0, 1.0, 0.0);
-    let camera = Camera::new(origin, target, vertical, focal_length, aperture, viewport);
+    let camera = Camera::with_fov(origin, target, vertical, 40.0, 16.0, aspect_ratio);
 
     // Small sample scene containing sample surfaces.
-    let scene = Scene {
-        objects: vec![
-            // Left side hollow dielectric sphere.
-            Object {
-                surface: Box::new(Sphere {
-                    center: Point3(-1.0, 0.0, -1.0),
-                    radius: 0.5,
-                }),
-                material: Box::new(Dielectric::new(Color(1.0, 1.0, 1.0), 1.5)),
-            },
-            Object {
-                surface: Box::new(Sphere {
-                    center: Point3(-1.0, 0.0, -1.0),
-                    radius: -0.4,
-                }),
-                material: Box::new(Dielectric::new(Color(1.0, 1.0, 1.0), 1.5)),
-            },
-            // Center diffuse sphere.
-            Object {
-                surface: Box::new(Sphere {
-                    center: Point3(0.0, 0.0, -1.0),
-                    radius: 0.5,
-                }),
-                material: Box::new(Lambertian::new(Color(0.1, 0.2, 0.5))),
-            },
-            // Right side metal sphere.
-            Object {
-                surface: Box::new(Sphere {
-                    center: Point3(1.0, 0.0, -1.0),
-                    radius: 0.5,
-                }),
-                material: Box::new(Metal::new(Color(0.8, 0.6, 0.2), 0.0)),
-            },
-            // "Ground" sphere.
-            Object {
-                surface: Box::new(Sphere {
-                    center: Point3(0.0, -100.5, -1.0),
-                    radius: 100.0,
-                }),
-                material: Box::new(Hemispherical::new(Color(0.8, 0.8, 0.0))),
-            },
-        ],
-    };
+    let scene = Scene::from_objects(vec![
+        // Left side hollow dielectric sphere.
+        Object {
+            surface: Box::new(Sphere {
+                center: Point3(-1.0, 0.0, -1.0),
+                radius: 0.5,
+            }),
+            material: Box::new(Dielectric::new(Color(1.0, 1.0, 1.0), 1.5)),
+        },
+        Object {
+            surface: Box::new(Sphere {
+                center: Point3(-1.0, 0.0, -1.0),
+                radius: -0.4,
+            }),
+            material: Box::new(Dielectric::new(Color(1.0, 1.0, 1.0), 1.5)),
+        },
+        // Center diffuse sphere.
+        Object {
+            surface: Box::new(Sphere {
+                center: Point3(0.0, 0.0, -1.0),
+                radius: 0.5,
+            }),
+            material: Box::new(Lambertian::new(Color(0.1, 0.2, 0.5))),
+        },
+        // Right side metal sphere.
+        Object {
+            surface: Box::new(Sphere {
+                center: Point3(1.0, 0.0, -1.0),
+                radius: 0.5,
+            }),
+            material: Box::new(Metal::new(Color(0.8, 0.6, 0.2), 0.0)),
+        },
+        // "Ground" sphere.
+        Object {
+            surface: Box::new(Sphere {
+                center: Point3(0.0, -100.5, -1.0),
+                radius: 100.0,
+            }),
+            material: Box::new(Hemispherical::new(Color(0.8, 0.8, 0.0))),
+        },
+    ]);
 
     // Return the camera & scene.
     (camera, scene)
 }
 
 fn get_large_scene(aspect_ratio: f32) -> (Camera, Scene) {
-    // Viewport size.
-    let viewport = (2.0 * aspect_ratio, 2.0_f32);
-
-    // The angle of view can be determined using the diagonal image plane dimension `d`
-    // and the focal length `f`, using the relation `aov = 2 * arctan(d / 2f)`. Working
-    // backwards, we can calculate `f` as `f = d / (2 * tan(aov / 2))`.
+    // Camera definition
     // Note: Since we use diagonal field-of-view, exact angles will differ compared to
     // the book (depending on aspect ratio).
-    let angle_of_view = 36.0_f32.to_radians();
-    let diagonal = (viewport.0.powi(2) + viewport.1.powi(2)).sqrt();
-    let focal_length = (diagonal / 2.0) / (angle_of_view / 2.0).tan();
-    let aperture = 32.0;
-
-    // Camera definition
     let origin = Point3(13.0, 2.0, 3.0);
     let target = Point3(3.36376, 0.517501, 0.776252);
     let vertical = Vect3(0.0, 1.0, 0.0);
-    let camera = Camera::new(origin, target, vertical, focal_length, aperture, viewport);
+    let camera = Camera::with_fov(origin, target, vertical, 36.0, 32.0, aspect_ratio);
 
     // Large sample scene containing sample surfaces.
-    let mut scene = Scene {
-        objects: vec![
-            // Large dielectric sphere.
-            Object {
-                surface: Box::new(Sphere {
-                    center: Point3(0.0, 1.0, 0.0),
-                    radius: 1.0,
-                }),
-                material: Box::new(Dielectric::new(Color(1.0, 1.0, 1.0), 1.5)),
-            },
-            // Large diffuse sphere.
-            Object {
-                surface: Box::new(Sphere {
-                    center: Point3(-4.0, 1.0, 0.0),
-                    radius: 1.0,
-                }),
-                material: Box::new(Lambertian::new(Color(0.4, 0.2, 0.1))),
-            },
-            // Large metal sphere
-            Object {
-                surface: Box::new(Sphere {
-                    center: Point3(4.0, 1.0, 0.0),
-                    radius: 1.0,
-                }),
-                material: Box::new(Metal::new(Color(0.7, 0.6, 0.5), 0.0)),
-            },
-            // "Ground" sphere.
-            Object {
-                surface: Box::new(Sphere {
-                    center: Point3(0.0, -1000.0, 0.0),
-                    radius: 1000.0,
-                }),
-                material: Box::new(Hemispherical::new(Color(0.5, 0.5, 0.5))),
-            },
-        ],
-    };
+    let mut scene = Scene::from_objects(vec![
+        // Large dielectric sphere.
+        Object {
+            surface: Box::new(Sphere {
+                center: Point3(0.0, 1.0, 0.0),
+                radius: 1.0,
+            }),
+            material: Box::new(Dielectric::new(Color(1.0, 1.0, 1.0), 1.5)),
+        },
+        // Large diffuse sphere.
+        Object {
+            surface: Box::new(Sphere {
+                center: Point3(-4.0, 1.0, 0.0),
+                radius: 1.0,
+            }),
+            material: Box::new(Lambertian::new(Color(0.4, 0.2, 0.1))),
+        },
+        // Large metal sphere
+        Object {
+            surface: Box::new(Sphere {
+                center: Point3(4.0, 1.0, 0.0),
+                radius: 1.0,
+            }),
+            material: Box::new(Metal::new(Color(0.7, 0.6, 0.5), 0.0)),
+        },
+        // "Ground" sphere.
+        Object {
+            surface: Box::new(Sphere {
+                center: Point3(0.0, -1000.0, 0.0),
+                radius: 1000.0,
+            }),
+            material: Box::new(Hemispherical::new(Color(0.5, 0.5, 0.5))),
+        },
+    ]);
 
-    let mut rng = thread_rng();
+    let mut rng = sampler::rng();
     let uniform = Uniform::new(0.0, 1.0);
     for a in -11..11 {
         for b in -11..11 {
@@ -152,14 +146,18 @@ fn get_large_scene(aspect_ratio: f32) -> (Camera, Scene) {
                 0.2,
                 (b as f32) + (0.9 * rng.sample(uniform)),
             );
-            let material: Box<dyn Material> = match rng.sample(uniform) {
+            let sphere = Sphere {
+                center,
+                radius: 0.2,
+            };
+            match rng.sample(uniform) {
                 r if r < 0.8 => {
                     let color = Color(
                         rng.sample(uniform) * rng.sample(uniform),
                         rng.sample(uniform) * rng.sample(uniform),
                         rng.sample(uniform) * rng.sample(uniform),
                     );
-                    Box::new(Lambertian::new(color))
+                    scene.add(sphere, Lambertian::new(color));
                 }
                 r if r < 0.95 => {
                     let color = Color(
@@ -167,96 +165,855 @@ fn get_large_scene(aspect_ratio: f32) -> (Camera, Scene) {
                         0.5 + 0.5 * rng.sample(uniform),
                         0.5 + 0.5 * rng.sample(uniform),
                     );
-                    Box::new(Metal::new(color, 0.5 * rng.sample(uniform)))
+                    scene.add(sphere, Metal::new(color, 0.5 * rng.sample(uniform)));
                 }
-                _ => Box::new(Dielectric::new(Color(1.0, 1.0, 1.0), 1.5)),
-            };
-            scene.objects.push(Object {
-                surface: Box::new(Sphere {
-                    center,
-                    radius: 0.2,
-                }),
-                material,
-            });
+                _ => scene.add(sphere, Dielectric::new(Color(1.0, 1.0, 1.0), 1.5)),
+            }
         }
     }
+    scene.build_bvh();
 
     // Return the camera & scene.
     (camera, scene)
 }
 
+/// A function constructing a scene (and camera) for a given aspect ratio.
+type SceneConstructor = fn(f32) -> (Camera, Scene);
+
+/// Registry of pre-defined sample scenes, by name.
+const SCENES: &[(&str, SceneConstructor)] =
+    &[("small", get_small_scene), ("large", get_large_scene)];
+
+/// An error occurring when looking up a scene.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SceneError {
+    /// No scene with the given name exists.
+    Unknown(String),
+    /// The scene file could not be read.
+    Io(String),
+    /// The scene file is not a valid scene description.
+    Parse(String),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Unknown(name) => write!(f, "Unknown scene: {}", name),
+            SceneError::Io(error) => write!(f, "Could not read scene file: {}", error),
+            SceneError::Parse(error) => write!(f, "Invalid scene file: {}", error),
+        }
+    }
+}
+
+impl error::Error for SceneError {}
+
+/// List the names of all pre-defined sample scenes.
+pub fn list_scenes() -> Vec<&'static str> {
+    SCENES.iter().map(|(name, _)| *name).collect()
+}
+
 /// Get a pre-defined sample scene.
-pub fn get_scene(aspect_ratio: f32, scene: &str) -> (Camera, Scene) {
-    match scene {
-        "small" => get_small_scene(aspect_ratio),
-        "large" => get_large_scene(aspect_ratio),
-        _ => panic!("Unknown scene: {}", scene),
+///
+/// # Arguments
+///
+/// * `aspect_ratio` - aspect ratio of the rendered image
+/// * `scene` - name of the scene (see `list_scenes`)
+pub fn get_scene(aspect_ratio: f32, scene: &str) -> Result<(Camera, Scene), SceneError> {
+    SCENES
+        .iter()
+        .find(|(name, _)| *name == scene)
+        .map(|(_, constructor)| constructor(aspect_ratio))
+        .ok_or_else(|| SceneError::Unknown(scene.to_string()))
+}
+
+/// Load a scene from a JSON scene file (see `SceneDescription`).
+///
+/// # Arguments
+///
+/// * `aspect_ratio` - aspect ratio of the rendered image
+/// * `path` - path to the scene file
+pub fn load_scene(
+    aspect_ratio: f32,
+    path: impl AsRef<Path>,
+) -> Result<(Camera, Scene), SceneError> {
+    let json = fs::read_to_string(path).map_err(|error| SceneError::Io(error.to_string()))?;
+    let description =
+        SceneDescription::from_json(&json).map_err(|error| SceneError::Parse(error.to_string()))?;
+    Ok(description.build(aspect_ratio))
+}
+
+/// Settings controlling how the rays of each sample are traced.
+#[derive(Debug, Clone, Copy)]
+struct Tracing {
+    depth: usize,
+    cutoff: f32,
+    component: Component,
+    path_samples: usize,
+    seed: Option<u64>,
+}
+
+impl Tracing {
+    /// Trace the full light of rays up to a recursion depth.
+    fn new(depth: usize) -> Tracing {
+        Tracing {
+            depth,
+            cutoff: 0.0,
+            component: Component::Beauty,
+            path_samples: 1,
+            seed: None,
+        }
+    }
+
+    /// Render the color for a specific ray.
+    fn render_ray(&self, scene: &Scene, ray: &types::Ray) -> image::Pixel {
+        scene.render_ray_component(ray, self.depth, self.component, self.cutoff)
+    }
+}
+
+/// The progress of a render, passed to the render callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of rows rendered so far.
+    pub rows_done: usize,
+    /// The total number of rows in the image.
+    pub total_rows: usize,
+}
+
+impl Progress {
+    /// The fraction of rows rendered so far, between `0.0` and `1.0`.
+    pub fn fraction(&self) -> f32 {
+        if self.total_rows == 0 {
+            1.0
+        } else {
+            (self.rows_done as f32) / (self.total_rows as f32)
+        }
     }
 }
 
 /// Render an image by raytracing.
 ///
+/// Each pixel is sampled at `aa_samples` positions within the pixel (which
+/// anti-aliases edges), and `path_samples` paths are traced through each of
+/// these positions (which reduces the noise of the lighting).
+///
+/// Paths are cut short once their throughput drops below the contribution
+/// cutoff (see `Scene::render_ray_component`); use `0.0` to always trace
+/// paths up to the recursion depth.
+///
+/// The image is rendered in parallel, in square tiles. If a `cancel` flag is
+/// given, it is checked before each tile is rendered. Once it is set, the
+/// remaining tiles are skipped (and left black), so the partially rendered
+/// image is returned early.
+///
+/// The image is allocated and rendered into (see `render_into`), so its
+/// size must match the aspect ratio of the camera viewport.
+///
 /// # Arguments
 ///
 /// * `scene` - scene to render
 /// * `width` - output image width
 /// * `height` - output image height
-/// * `samples` - samples per pixel
+/// * `aa_samples` - sample positions per pixel
+/// * `path_samples` - paths traced per sample position
 /// * `depth` - recursion depth
-/// * `callback` - callback called when a row has been rendered
+/// * `contribution_cutoff` - smallest path throughput to keep tracing
+/// * `cancel` - flag aborting the render once set
+/// * `callback` - callback called with the progress when a row has been rendered
+#[allow(clippy::too_many_arguments)]
 pub fn render<F>(
+    scene: &Scene,
+    camera: &Camera,
+    width: usize,
+    height: usize,
+    aa_samples: usize,
+    path_samples: usize,
+    depth: usize,
+    contribution_cutoff: f32,
+    cancel: Option<&AtomicBool>,
+    callback: F,
+) -> Image
+where
+    F: FnMut(Progress) + Send,
+{
+    let tracing = Tracing {
+        cutoff: contribution_cutoff,
+        path_samples,
+        ..Tracing::new(depth)
+    };
+    let mut image = Image::new(width, height);
+    render_into_with(
+        &mut image, scene, camera, aa_samples, tracing, cancel, callback,
+    );
+    image
+}
+
+/// Render a single component of the light in an image by raytracing.
+///
+/// See `Scene::render_ray_component` for how the components are separated.
+///
+/// # Arguments
+///
+/// * `scene` - scene to render
+/// * `camera` - camera to render from
+/// * `width` - output image width
+/// * `height` - output image height
+/// * `samples` - samples per pixel
+/// * `depth` - recursion depth
+/// * `component` - the component of the light to render
+/// * `callback` - callback called with the progress when a row has been rendered
+#[allow(clippy::too_many_arguments)]
+pub fn render_component<F>(
     scene: &Scene,
     camera: &Camera,
     width: usize,
     height: usize,
     samples: usize,
     depth: usize,
-    mut callback: F,
+    component: Component,
+    callback: F,
 ) -> Image
 where
-    F: FnMut(usize),
+    F: FnMut(Progress) + Send,
 {
+    let tracing = Tracing {
+        component,
+        ..Tracing::new(depth)
+    };
     let mut image = Image::new(width, height);
-    let mut rng = thread_rng();
-
-    // Render the image!
-    for (y, row) in image.iter_mut().rev().enumerate() {
-        for (x, pixel) in row.iter_mut().enumerate() {
-            let acc = (0..samples)
-                .map(|_| {
-                    let u = ((x as f32) + rng.gen_range(0.0..1.0)) / ((width as f32) - 1.0);
-                    let v = ((y as f32) + rng.gen_range(0.0..1.0)) / ((height as f32) - 1.0);
-                    scene.render_ray(&camera.ray(u, v), depth)
+    render_into_with(&mut image, scene, camera, samples, tracing, None, callback);
+    image
+}
+
+/// Render an image of a single object against the default background.
+///
+/// # Arguments
+///
+/// * `surface` - surface of the object to render
+/// * `material` - material of the object to render
+/// * `camera` - camera to render from
+/// * `width` - output image width
+/// * `height` - output image height
+/// * `samples` - samples per pixel
+/// * `depth` - recursion depth
+pub fn render_object(
+    surface: impl Surface + 'static,
+    material: impl Material + 'static,
+    camera: &Camera,
+    width: usize,
+    height: usize,
+    samples: usize,
+    depth: usize,
+) -> Image {
+    let scene = Scene::from_objects(vec![Object {
+        surface: Box::new(surface),
+        material: Box::new(material),
+    }]);
+    render(
+        &scene,
+        camera,
+        width,
+        height,
+        samples,
+        1,
+        depth,
+        0.0,
+        None,
+        |_| (),
+    )
+}
+
+/// Render an anaglyph 3D image by raytracing a stereo pair.
+///
+/// Both eyes are rendered separately (see `Camera::stereo_pair`), and their
+/// luminance is combined into a single image according to `mode`.
+///
+/// # Arguments
+///
+/// * `scene` - scene to render
+/// * `camera` - camera to render from, centered between the eyes
+/// * `ipd` - interpupillary distance, i.e. the distance between the eyes
+/// * `width` - output image width
+/// * `height` - output image height
+/// * `samples` - samples per pixel
+/// * `depth` - recursion depth
+/// * `mode` - the color scheme used to combine the eyes
+#[allow(clippy::too_many_arguments)]
+pub fn render_anaglyph(
+    scene: &Scene,
+    camera: &Camera,
+    ipd: f32,
+    width: usize,
+    height: usize,
+    samples: usize,
+    depth: usize,
+    mode: AnaglyphMode,
+) -> Image {
+    let (left, right) = camera.stereo_pair(ipd);
+    let left = render(
+        scene,
+        &left,
+        width,
+        height,
+        samples,
+        1,
+        depth,
+        0.0,
+        None,
+        |_| (),
+    );
+    let right = render(
+        scene,
+        &right,
+        width,
+        height,
+        samples,
+        1,
+        depth,
+        0.0,
+        None,
+        |_| (),
+    );
+    Image::anaglyph(&left, &right, mode)
+}
+
+/// Auxiliary render passes, rendered along with the color of an image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aov {
+    /// The rendered color of each pixel.
+    pub color: Image,
+    /// The world-space normal at the first hit of each pixel, encoded as
+    /// `n * 0.5 + 0.5` (pixels missing the scene are black).
+    pub normal: Image,
+    /// The distance to the first hit of each pixel, divided by the far plane
+    /// and clamped to `[0, 1]`, in every channel (pixels missing the scene
+    /// are white).
+    pub depth: Image,
+}
+
+/// Render an image by raytracing, along with normal and depth buffers.
+///
+/// The normal and depth buffers only trace a single primary ray through
+/// the center of each pixel, without any recursion.
+///
+/// # Arguments
+///
+/// * `scene` - scene to render
+/// * `camera` - camera to render from
+/// * `width` - output image width
+/// * `height` - output image height
+/// * `samples` - samples per pixel
+/// * `depth` - recursion depth
+/// * `far` - distance mapped to white in the depth buffer
+pub fn render_aov(
+    scene: &Scene,
+    camera: &Camera,
+    width: usize,
+    height: usize,
+    samples: usize,
+    depth: usize,
+    far: f32,
+) -> Aov {
+    let color = render(
+        scene,
+        camera,
+        width,
+        height,
+        samples,
+        1,
+        depth,
+        0.0,
+        None,
+        |_| (),
+    );
+    let mut normal = Image::new(width, height);
+    let mut distance = Image::new(width, height);
+    normal
+        .par_iter_mut()
+        .zip(distance.par_iter_mut())
+        .rev()
+        .enumerate()
+        .for_each(|(y, (normals, distances))| {
+            for x in 0..width {
+                let u = ((x as f32) + 0.5) / ((width as f32) - 1.0);
+                let v = ((y as f32) + 0.5) / ((height as f32) - 1.0);
+                let (n, d) = match scene.first_hit(&camera.ray(u, v)) {
+                    Some(hit) => (
+                        hit.normal() * 0.5 + Vect3(0.5, 0.5, 0.5),
+                        (hit.distance() / far).min(1.0),
+                    ),
+                    None => (Vect3::zero(), 1.0),
+                };
+                normals[x] = image::Pixel(n.x(), n.y(), n.z());
+                distances[x] = image::Pixel(d, d, d);
+            }
+        });
+    Aov {
+        color,
+        normal,
+        depth: distance,
+    }
+}
+
+/// Render an image by raytracing into an existing image buffer.
+///
+/// Square tiles of the image are rendered in parallel, and `callback` is
+/// called with the progress each time a row has been rendered, i.e. all tiles
+/// covering it (so rows may finish in any order, but the number of completed
+/// rows is strictly increasing).
+///
+/// Every pixel of the buffer is overwritten. The buffer must match the
+/// aspect ratio of the camera viewport to within a pixel, or this function
+/// will panic.
+///
+/// # Arguments
+///
+/// * `image` - image buffer to render into
+/// * `scene` - scene to render
+/// * `camera` - camera to render from
+/// * `samples` - samples per pixel
+/// * `depth` - recursion depth
+/// * `callback` - callback called with the progress when a row has been rendered
+pub fn render_into<F>(
+    image: &mut Image,
+    scene: &Scene,
+    camera: &Camera,
+    samples: usize,
+    depth: usize,
+    callback: F,
+) where
+    F: FnMut(Progress) + Send,
+{
+    let tracing = Tracing::new(depth);
+    render_into_with(image, scene, camera, samples, tracing, None, callback);
+}
+
+/// Side length (in pixels) of the square tiles images are rendered in.
+const TILE_SIZE: usize = 16;
+
+/// Render an image buffer, tile by tile, until (optionally) cancelled.
+///
+/// This is `render_into` with full control over the tracing, and panics if
+/// the buffer does not match the aspect ratio of the camera viewport.
+fn render_into_with<F>(
+    image: &mut Image,
+    scene: &Scene,
+    camera: &Camera,
+    samples: usize,
+    tracing: Tracing,
+    cancel: Option<&AtomicBool>,
+    callback: F,
+) where
+    F: FnMut(Progress) + Send,
+{
+    let width = image.width();
+    let height = image.height();
+    assert!(
+        ((width as f32) - (height as f32) * camera.aspect_ratio()).abs() <= 1.0,
+        "Image size {}x{} does not match the camera aspect ratio {}",
+        width,
+        height,
+        camera.aspect_ratio()
+    );
+    render_tiles(image, TILE_SIZE, cancel, callback, |x, y| {
+        render_pixel(scene, camera, (x, y), (width, height), samples, tracing)
+    });
+}
+
+/// Render an image buffer, tile by tile.
+///
+/// The image is split into `tile` by `tile` tiles which are rendered in
+/// parallel. Compared to rendering whole rows in parallel, this balances the
+/// load better for scenes where some parts of the image are much cheaper to
+/// render than others, and keeps neighbouring rays (hitting the same
+/// surfaces) on the same thread.
+///
+/// The `cancel` flag is checked before each tile is rendered, and skipped
+/// tiles are left untouched. The `callback` is called once for every row,
+/// as soon as all tiles covering it have been rendered.
+///
+/// Note that `pixel` is given coordinates with their origin in the bottom
+/// left corner, just like `render_pixel`.
+fn render_tiles<F, P>(
+    image: &mut Image,
+    tile: usize,
+    cancel: Option<&AtomicBool>,
+    callback: F,
+    pixel: P,
+) where
+    F: FnMut(Progress) + Send,
+    P: Fn(usize, usize) -> image::Pixel + Sync,
+{
+    let width = image.width();
+    let height = image.height();
+    let tiles: Vec<_> = (0..height)
+        .step_by(tile)
+        .flat_map(|row| (0..width).step_by(tile).map(move |col| (col, row)))
+        .collect();
+
+    // Render the tiles!
+    let callback = Mutex::new(callback);
+    let completed = AtomicUsize::new(0);
+    let tiles_per_row = width.div_ceil(tile);
+    let pending: Vec<_> = (0..height.div_ceil(tile))
+        .map(|_| AtomicUsize::new(tiles_per_row))
+        .collect();
+    let rendered: Vec<Option<Vec<image::Pixel>>> = tiles
+        .par_iter()
+        .map(|(col, row)| {
+            if cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+                return None;
+            }
+            let cols = *col..(col + tile).min(width);
+            let rows = *row..(row + tile).min(height);
+            let pixels = rows
+                .clone()
+                .flat_map(|r| cols.clone().map(move |x| (x, height - 1 - r)))
+                .map(|(x, y)| pixel(x, y))
+                .collect();
+            if pending[row / tile].fetch_sub(1, Ordering::SeqCst) == 1 {
+                let mut callback = callback.lock().unwrap();
+                for _ in rows {
+                    callback(Progress {
+                        rows_done: completed.fetch_add(1, Ordering::SeqCst) + 1,
+                        total_rows: height,
+                    });
+                }
+            }
+            Some(pixels)
+        })
+        .collect();
+
+    // Copy the tiles into the image.
+    for ((col, row), pixels) in tiles.into_iter().zip(rendered) {
+        let tile_width = (col + tile).min(width) - col;
+        for (index, pixel) in pixels.into_iter().flatten().enumerate() {
+            image[row + index / tile_width][col + index % tile_width] = pixel;
+        }
+    }
+}
+
+/// Render an image, adaptively supersampling pixels along edges.
+///
+/// A first pass renders the image using `base_samples` samples per pixel.
+/// Pixels whose color differs from any of their direct neighbours by more
+/// than `edge_threshold` (in any channel) are then rendered again using
+/// `edge_samples` samples per pixel.
+///
+/// Returns the image along with the number of samples used for each pixel,
+/// in row-major order.
+///
+/// # Arguments
+///
+/// * `scene` - scene to render
+/// * `camera` - camera to render from
+/// * `width` - output image width
+/// * `height` - output image height
+/// * `base_samples` - samples per pixel in the first pass
+/// * `edge_samples` - samples per pixel for edge pixels
+/// * `edge_threshold` - smallest channel difference considered an edge
+/// * `depth` - recursion depth
+#[allow(clippy::too_many_arguments)]
+pub fn render_edge_adaptive(
+    scene: &Scene,
+    camera: &Camera,
+    width: usize,
+    height: usize,
+    base_samples: usize,
+    edge_samples: usize,
+    edge_threshold: f32,
+    depth: usize,
+) -> (Image, Vec<usize>) {
+    let base = render(
+        scene,
+        camera,
+        width,
+        height,
+        base_samples,
+        1,
+        depth,
+        0.0,
+        None,
+        |_| (),
+    );
+    let differs = |a: &image::Pixel, b: &image::Pixel| {
+        let diff = a - b;
+        diff.red()
+            .abs()
+            .max(diff.green().abs())
+            .max(diff.blue().abs())
+            > edge_threshold
+    };
+    let mut image = Image::new(width, height);
+    let mut samples = vec![base_samples; width * height];
+    for (row, pixels) in image.iter_mut().enumerate() {
+        for (x, pixel) in pixels.iter_mut().enumerate() {
+            let neighbours = [
+                (x.checked_sub(1), Some(row)),
+                (Some(x + 1).filter(|x| *x < width), Some(row)),
+                (Some(x), row.checked_sub(1)),
+                (Some(x), Some(row + 1).filter(|y| *y < height)),
+            ];
+            let edge = neighbours.iter().any(|neighbour| match neighbour {
+                (Some(nx), Some(ny)) => differs(&base[row][x], &base[*ny][*nx]),
+                _ => false,
+            });
+            *pixel = if edge {
+                samples[row * width + x] = edge_samples;
+                let y = height - row - 1;
+                render_pixel(
+                    scene,
+                    camera,
+                    (x, y),
+                    (width, height),
+                    edge_samples,
+                    Tracing::new(depth),
+                )
+            } else {
+                base[row][x]
+            };
+        }
+    }
+    (image, samples)
+}
+
+/// Render a single pixel by averaging a number of samples.
+///
+/// Each of the `samples` positions within the pixel is traced as many times
+/// as the path samples of `tracing`.
+///
+/// If `tracing` is seeded, the random number generator is reseeded for the
+/// pixel first, so the pixel is rendered the same regardless of the order in
+/// which pixels are rendered (or the thread rendering it).
+///
+/// Note that `pixel` is given with its origin in the bottom left corner.
+fn render_pixel(
+    scene: &Scene,
+    camera: &Camera,
+    pixel: (usize, usize),
+    size: (usize, usize),
+    samples: usize,
+    tracing: Tracing,
+) -> image::Pixel {
+    if let Some(seed) = tracing.seed {
+        sampler::seed_pixel(seed, (pixel.1 * size.0 + pixel.0) as u64);
+    }
+    let acc = pixel_offsets(samples)
+        .into_iter()
+        .flat_map(|offset| iter::repeat_n(offset, tracing.path_samples))
+        .map(|offset| sample_pixel(scene, camera, pixel, size, offset, tracing))
+        .fold(image::Pixel::default(), |acc, pixel| acc + pixel);
+    acc / ((samples * tracing.path_samples) as f32)
+}
+
+/// Pick the positions of a number of samples within a pixel.
+///
+/// If `samples` is a perfect square, the pixel is divided into a grid of
+/// equally sized cells, and each sample is randomly jittered within its own
+/// cell (i.e. stratified sampling). Otherwise, each sample is randomly
+/// jittered within the whole pixel.
+fn pixel_offsets(samples: usize) -> Vec<(f32, f32)> {
+    let mut rng = sampler::rng();
+    let cells = (samples as f64).sqrt().round() as usize;
+    if cells * cells == samples {
+        let size = (cells as f32).recip();
+        (0..samples)
+            .map(|index| {
+                let (x, y) = ((index % cells) as f32, (index / cells) as f32);
+                (
+                    (x + rng.gen_range(0.0..1.0)) * size,
+                    (y + rng.gen_range(0.0..1.0)) * size,
+                )
+            })
+            .collect()
+    } else {
+        (0..samples)
+            .map(|_| (rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0)))
+            .collect()
+    }
+}
+
+/// Trace a single sample through a pixel, at an offset within the pixel.
+///
+/// Note that `pixel` is given with its origin in the bottom left corner.
+fn sample_pixel(
+    scene: &Scene,
+    camera: &Camera,
+    pixel: (usize, usize),
+    size: (usize, usize),
+    offset: (f32, f32),
+    tracing: Tracing,
+) -> image::Pixel {
+    let u = ((pixel.0 as f32) + offset.0) / ((size.0 as f32) - 1.0);
+    let v = ((pixel.1 as f32) + offset.1) / ((size.1 as f32) - 1.0);
+    tracing.render_ray(scene, &camera.ray(u, v))
+}
+
+/// Estimate the number of samples per pixel needed to reach a noise level.
+///
+/// A small pilot pass is rendered to measure the per-pixel variance of the
+/// luminance. Since the variance of the mean falls off as `1/N`, the number
+/// of samples needed for the average standard error to reach `target_noise`
+/// can then be extrapolated.
+///
+/// # Arguments
+///
+/// * `scene` - scene to render
+/// * `camera` - camera to render from
+/// * `width` - output image width
+/// * `height` - output image height
+/// * `depth` - recursion depth
+/// * `target_noise` - target standard error of the pixel luminance
+pub fn estimate_samples_for_noise(
+    scene: &Scene,
+    camera: &Camera,
+    width: usize,
+    height: usize,
+    depth: usize,
+    target_noise: f32,
+) -> usize {
+    const PILOT_SAMPLES: usize = 8;
+    let mut variance = 0.0;
+    for y in 0..height {
+        for x in 0..width {
+            let samples: Vec<f32> = pixel_offsets(PILOT_SAMPLES)
+                .into_iter()
+                .map(|offset| {
+                    let tracing = Tracing::new(depth);
+                    sample_pixel(scene, camera, (x, y), (width, height), offset, tracing)
+                        .luminance()
                 })
-                .fold(image::Pixel::default(), |acc, pixel| acc + pixel);
-            *pixel = acc / (samples as f32);
+                .collect();
+            let mean = samples.iter().sum::<f32>() / (PILOT_SAMPLES as f32);
+            variance += samples.iter().map(|s| (s - mean).powi(2)).sum::<f32>()
+                / ((PILOT_SAMPLES - 1) as f32);
         }
-        callback(y + 1);
     }
+    variance /= (width * height) as f32;
+    ((variance / target_noise.powi(2)).ceil() as usize).max(1)
+}
 
-    image
+/// An image file format, which an image can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Plain-text PPM (`P3`), see `write_pgm`.
+    Pgm,
+    /// Binary PPM (`P6`), see `write_ppm_binary`.
+    Ppm,
+    /// PNG, see `write_png`.
+    Png,
+    /// Radiance HDR, see `write_hdr`.
+    Hdr,
+}
+
+impl FromStr for ImageFormat {
+    type Err = String;
+
+    /// Parse an image format from its (case-insensitive) name, i.e. one of
+    /// `pgm`, `ppm`, `png` or `hdr`.
+    fn from_str(name: &str) -> Result<ImageFormat, String> {
+        match name.to_lowercase().as_str() {
+            "pgm" => Ok(ImageFormat::Pgm),
+            "ppm" => Ok(ImageFormat::Ppm),
+            "png" => Ok(ImageFormat::Png),
+            "hdr" => Ok(ImageFormat::Hdr),
+            _ => Err(format!("Unknown image format: {}", name)),
+        }
+    }
+}
+
+impl ImageFormat {
+    /// Guess the image format from the extension of a file name.
+    ///
+    /// Returns an `InvalidInput` error if the extension is missing or unknown.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<ImageFormat, io::Error> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        match extension.map(str::parse) {
+            Some(Ok(format)) => Ok(format),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unsupported image format: {}", path.display()),
+            )),
+        }
+    }
+
+    /// Write an image to a writer/sink, in this format.
+    ///
+    /// The gamma correction and tone mapping are ignored by formats storing
+    /// linear values (i.e. HDR).
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - writer/sink to serialize image into
+    /// * `image` - image to serialize
+    /// * `gamma` - gamma correction to apply
+    /// * `tonemap` - tone-mapping operator to apply before gamma correction
+    /// * `callback` - callback called when a row has been rendered
+    pub fn write<F>(
+        self,
+        stream: &mut dyn io::Write,
+        image: &Image,
+        gamma: f32,
+        tonemap: ToneMap,
+        callback: F,
+    ) -> Result<(), io::Error>
+    where
+        F: FnMut(usize),
+    {
+        match self {
+            ImageFormat::Pgm => write_pgm(stream, image, gamma, tonemap, callback),
+            ImageFormat::Ppm => write_ppm_binary(stream, image, gamma, tonemap, callback),
+            ImageFormat::Png => write_png(stream, image, gamma, tonemap, callback),
+            ImageFormat::Hdr => write_hdr(stream, image, callback),
+        }
+    }
+}
+
+/// Save an image to a file, in the format given by its extension.
+///
+/// # Arguments
+///
+/// * `image` - image to save
+/// * `path` - path of the file (see `ImageFormat::from_path`)
+/// * `gamma` - gamma correction to apply
+/// * `tonemap` - tone-mapping operator to apply before gamma correction
+pub fn save_image(
+    image: &Image,
+    path: impl AsRef<Path>,
+    gamma: f32,
+    tonemap: ToneMap,
+) -> Result<(), io::Error> {
+    let format = ImageFormat::from_path(&path)?;
+    let mut file = io::BufWriter::new(fs::File::create(path)?);
+    format.write(&mut file, image, gamma, tonemap, |_: usize| ())?;
+    file.flush()
 }
 
 /// Serialize an image using the PGM format.
 ///
+/// Channel values are clamped into `[0, 1]` and gamma-corrected before
+/// being written.
+///
 /// # Arguments
 ///
 /// * `stream` - writer/sink to serialize image into
 /// * `image` - image to serialize
 /// * `gamma` - gamma correction to apply
+/// * `tonemap` - tone-mapping operator to apply before gamma correction
 /// * `callback` - callback called when a row has been rendered
 ///
 /// # Example
 ///
 /// ```
-/// use raytrust::{Image, write_pgm};
+/// use raytrust::{Image, ToneMap, write_pgm};
 /// let image = Image::new(8, 8);
-/// write_pgm(&mut std::io::stdout(), &image, 2.2, |_: usize| ());
+/// write_pgm(&mut std::io::stdout(), &image, 2.2, ToneMap::None, |_: usize| ());
 /// ```
 pub fn write_pgm<F>(
-    stream: &mut (dyn io::Write),
+    stream: &mut dyn io::Write,
     image: &Image,
     gamma: f32,
+    tonemap: ToneMap,
     mut callback: F,
 ) -> Result<(), io::Error>
 where
@@ -267,19 +1024,158 @@ where
     writeln!(stream, "255")?;
     for (y, row) in image.iter().enumerate() {
         for pixel in row {
-            writeln!(
-                stream,
-                "{} {} {}",
-                ((pixel.red().powf(gamma.recip()) * 255.0).round() as u8),
-                ((pixel.green().powf(gamma.recip()) * 255.0).round() as u8),
-                ((pixel.blue().powf(gamma.recip()) * 255.0).round() as u8)
-            )?;
+            let [red, green, blue] = tonemap::apply(*pixel, tonemap).to_rgb8(gamma);
+            writeln!(stream, "{} {} {}", red, green, blue)?;
         }
         callback(y + 1);
     }
     Ok(())
 }
 
+/// Write an image to a writer/sink, in binary PPM (`P6`) format.
+///
+/// Channel values are clamped into `[0, 1]` and gamma-corrected before
+/// being written as one byte each.
+///
+/// # Arguments
+///
+/// * `stream` - writer/sink to serialize image into
+/// * `image` - image to serialize
+/// * `gamma` - gamma correction to apply
+/// * `tonemap` - tone-mapping operator to apply before gamma correction
+/// * `callback` - callback called when a row has been rendered
+///
+/// # Example
+///
+/// ```
+/// use raytrust::{Image, ToneMap, write_ppm_binary};
+/// let image = Image::new(8, 8);
+/// write_ppm_binary(&mut std::io::sink(), &image, 2.2, ToneMap::None, |_: usize| ());
+/// ```
+pub fn write_ppm_binary<F>(
+    stream: &mut dyn io::Write,
+    image: &Image,
+    gamma: f32,
+    tonemap: ToneMap,
+    mut callback: F,
+) -> Result<(), io::Error>
+where
+    F: FnMut(usize),
+{
+    writeln!(stream, "P6")?;
+    writeln!(stream, "{} {}", image.width(), image.height())?;
+    writeln!(stream, "255")?;
+    for (y, row) in image.iter().enumerate() {
+        let bytes: Vec<u8> = row
+            .iter()
+            .flat_map(|pixel| tonemap::apply(*pixel, tonemap).to_rgb8(gamma))
+            .collect();
+        stream.write_all(&bytes)?;
+        callback(y + 1);
+    }
+    Ok(())
+}
+
+/// Encode a pixel using a shared exponent (as in the Radiance RGBE format).
+fn encode_rgbe(pixel: &image::Pixel) -> [u8; 4] {
+    let channels = [pixel.red(), pixel.green(), pixel.blue()].map(|value| value.max(0.0));
+    let max = channels[0].max(channels[1]).max(channels[2]);
+    if max < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+    // Pick the exponent so that the mantissa of the largest channel is in
+    // the range `[0.5, 1.0)`, i.e. `max = mantissa * 2^exponent`.
+    let exponent = max.log2().floor() as i32 + 1;
+    let scale = 256.0 / 2.0_f32.powi(exponent);
+    let [r, g, b] = channels.map(|value| (value * scale).min(255.0) as u8);
+    [r, g, b, (exponent + 128) as u8]
+}
+
+/// Write an image to a writer/sink, in Radiance HDR (RGBE) format.
+///
+/// Pixel values are stored without gamma correction or clamping (except
+/// for negative values), preserving the dynamic range of the image.
+///
+/// # Arguments
+///
+/// * `stream` - writer/sink to serialize image into
+/// * `image` - image to serialize
+/// * `callback` - callback called when a row has been rendered
+///
+/// # Example
+///
+/// ```
+/// use raytrust::{Image, write_hdr};
+/// let image = Image::new(8, 8);
+/// write_hdr(&mut std::io::sink(), &image, |_: usize| ());
+/// ```
+pub fn write_hdr<F>(
+    stream: &mut dyn io::Write,
+    image: &Image,
+    mut callback: F,
+) -> Result<(), io::Error>
+where
+    F: FnMut(usize),
+{
+    writeln!(stream, "#?RADIANCE")?;
+    writeln!(stream, "FORMAT=32-bit_rle_rgbe")?;
+    writeln!(stream)?;
+    writeln!(stream, "-Y {} +X {}", image.height(), image.width())?;
+    for (y, row) in image.iter().enumerate() {
+        let bytes: Vec<u8> = row.iter().flat_map(encode_rgbe).collect();
+        stream.write_all(&bytes)?;
+        callback(y + 1);
+    }
+    Ok(())
+}
+
+/// Write an image to a writer/sink, in PNG format.
+///
+/// Channel values are clamped into `[0, 1]` and gamma-corrected before
+/// being encoded as 8-bit RGB.
+///
+/// # Arguments
+///
+/// * `stream` - writer/sink to serialize image into
+/// * `image` - image to serialize
+/// * `gamma` - gamma correction to apply
+/// * `tonemap` - tone-mapping operator to apply before gamma correction
+/// * `callback` - callback called when a row has been rendered
+///
+/// # Example
+///
+/// ```
+/// use raytrust::{Image, ToneMap, write_png};
+/// let image = Image::new(8, 8);
+/// write_png(&mut std::io::sink(), &image, 2.2, ToneMap::None, |_: usize| ());
+/// ```
+pub fn write_png<F>(
+    stream: &mut dyn io::Write,
+    image: &Image,
+    gamma: f32,
+    tonemap: ToneMap,
+    mut callback: F,
+) -> Result<(), io::Error>
+where
+    F: FnMut(usize),
+{
+    let mut encoder = png::Encoder::new(stream, image.width() as u32, image.height() as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    let mut data = writer.stream_writer()?;
+    for (y, row) in image.iter().enumerate() {
+        let bytes: Vec<u8> = row
+            .iter()
+            .flat_map(|pixel| tonemap::apply(*pixel, tonemap).to_rgb8(gamma))
+            .collect();
+        data.write_all(&bytes)?;
+        callback(y + 1);
+    }
+    data.finish()?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -292,7 +1188,7 @@ mod test {
         image[1][0] = image::Pixel(1.25, -1.25, 0.0);
 
         let mut vec: Vec<u8> = Vec::new();
-        write_pgm(&mut vec, &image, 1.0, |_: usize| ())?;
+        write_pgm(&mut vec, &image, 1.0, ToneMap::None, |_: usize| ())?;
 
         let expected = indoc::indoc! {"
             P3
@@ -305,4 +1201,560 @@ mod test {
         assert_eq!(expected, std::str::from_utf8(&vec).unwrap());
         Ok(())
     }
+
+    #[test]
+    fn test_write_pgm_gamma() -> Result<(), io::Error> {
+        let mut image = Image::new(1, 2);
+        image[0][0] = image::Pixel(1.0, 0.5, 0.0);
+        image[1][0] = image::Pixel(1.25, -1.25, 0.0);
+
+        let mut vec: Vec<u8> = Vec::new();
+        write_pgm(&mut vec, &image, 2.2, ToneMap::None, |_: usize| ())?;
+
+        let expected = indoc::indoc! {"
+            P3
+            1 2
+            255
+            255 186 0
+            255 0 0
+        "};
+
+        assert_eq!(expected, std::str::from_utf8(&vec).unwrap());
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_pgm_clamps_negative() -> Result<(), io::Error> {
+        let mut image = Image::new(1, 1);
+        image[0][0] = image::Pixel(-0.5, 0.25, 0.0);
+
+        let mut vec: Vec<u8> = Vec::new();
+        write_pgm(&mut vec, &image, 2.2, ToneMap::None, |_: usize| ())?;
+
+        assert_eq!(
+            "P3\n1 1\n255\n0 136 0\n",
+            std::str::from_utf8(&vec).unwrap()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_into_overwrites_buffer() {
+        let (camera, scene) = get_scene(2.0, "small").unwrap();
+        let filler = image::Pixel(-1.0, -1.0, -1.0);
+        let mut image = Image::new(8, 4);
+        for row in image.iter_mut() {
+            row.fill(filler);
+        }
+
+        let mut rows = 0;
+        render_into(&mut image, &scene, &camera, 1, 4, |_| rows += 1);
+        assert_eq!(rows, image.height());
+        for row in &image {
+            for pixel in row {
+                assert_ne!(*pixel, filler);
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_object() {
+        let camera = Camera::new(
+            Point3::zero(),
+            Point3(0.0, 0.0, -1.0),
+            Vect3(0.0, 1.0, 0.0),
+            1.0,
+            f32::INFINITY,
+            (2.0, 2.0),
+        );
+        let sphere = Sphere {
+            center: Point3(0.0, 0.0, -3.0),
+            radius: 1.0,
+        };
+        let material = Metal::new(Color(0.8, 0.2, 0.2), 0.0);
+        let scene = Scene::from_objects(vec![Object {
+            surface: Box::new(sphere),
+            material: Box::new(material),
+        }]);
+
+        let expected = render(&scene, &camera, 16, 16, 4, 1, 4, 0.0, None, |_| ());
+        let image = render_object(sphere, material, &camera, 16, 16, 4, 4);
+        let average = |image: &Image| {
+            let sum: f32 = image.iter().flatten().map(|pixel| pixel.luminance()).sum();
+            sum / ((image.width() * image.height()) as f32)
+        };
+        assert_eq!(image.width(), expected.width());
+        assert_eq!(image.height(), expected.height());
+        assert!((average(&image) - average(&expected)).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_render_anaglyph() {
+        let camera = Camera::new(
+            Point3::zero(),
+            Point3(0.0, 0.0, -1.0),
+            Vect3(0.0, 1.0, 0.0),
+            1.0,
+            f32::INFINITY,
+            (2.0, 2.0),
+        );
+        // A light straight ahead of the left eye, and to the left of the right eye.
+        let scene = Scene::from_objects(vec![Object {
+            surface: Box::new(Sphere {
+                center: Point3(-1.0, 0.0, -3.0),
+                radius: 1.0,
+            }),
+            material: Box::new(DiffuseLight::new(Color(1.0, 1.0, 1.0), 1.0)),
+        }])
+        .with_background(Background::Solid(Color(0.0, 0.0, 0.0)));
+        let (left, right) = camera.stereo_pair(2.0);
+        let left = render(&scene, &left, 16, 16, 4, 1, 2, 0.0, None, |_| ());
+        let right = render(&scene, &right, 16, 16, 4, 1, 2, 0.0, None, |_| ());
+
+        let image = render_anaglyph(&scene, &camera, 2.0, 16, 16, 4, 2, AnaglyphMode::RedCyan);
+        for x in [3, 8] {
+            assert_eq!(image[8][x].red(), left[8][x].luminance());
+            assert_eq!(image[8][x].blue(), right[8][x].luminance());
+        }
+        assert!(image[8][8].red() > 0.9 && image[8][8].blue() == 0.0);
+        assert!(image[8][3].red() == 0.0 && image[8][3].blue() > 0.9);
+    }
+
+    #[test]
+    fn test_render_edge_adaptive() {
+        struct Black;
+        impl Material for Black {
+            fn scatter_at(&self, _: &types::Ray, _: &Intersection) -> Vec<(types::Ray, Color)> {
+                vec![]
+            }
+        }
+        let origin = Point3::zero();
+        let target = Point3(0.0, 0.0, -1.0);
+        let vertical = Vect3(0.0, 1.0, 0.0);
+        let camera = Camera::new(origin, target, vertical, 1.0, f32::INFINITY, (2.0, 2.0));
+        let scene = Scene::from_objects(vec![Object {
+            surface: Box::new(Sphere {
+                center: Point3(0.0, 0.0, -3.0),
+                radius: 1.0,
+            }),
+            material: Box::new(Black),
+        }]);
+
+        // The sphere covers the center of the image, leaving the corners and
+        // edges of the image as flat background.
+        let (image, samples) = render_edge_adaptive(&scene, &camera, 16, 16, 1, 7, 0.2, 1);
+        assert_eq!(image.width(), 16);
+        assert!(samples[..32].iter().all(|count| *count == 1));
+        let row = &samples[8 * 16..9 * 16];
+        assert_eq!(row[0], 1);
+        assert_eq!(row[8], 1);
+        assert_eq!(row[15], 1);
+        assert!(row[1..8].contains(&7));
+        assert!(row[9..15].contains(&7));
+    }
+
+    #[test]
+    fn test_render_aov() {
+        let camera = Camera::new(
+            Point3::zero(),
+            Point3(0.0, 0.0, -1.0),
+            Vect3(0.0, 1.0, 0.0),
+            2.0,
+            f32::INFINITY,
+            (2.0, 2.0),
+        );
+        let scene = Scene::from_objects(vec![Object::new(
+            Sphere {
+                center: Point3(0.0, 0.0, -3.0),
+                radius: 1.0,
+            },
+            Lambertian::new(Color(0.5, 0.5, 0.5)),
+        )]);
+        let aov = render_aov(&scene, &camera, 17, 17, 1, 2, 10.0);
+
+        let center = aov.normal[8][8];
+        assert!((center.red() - 0.5).abs() < 0.05);
+        assert!((center.green() - 0.5).abs() < 0.05);
+        assert!((center.blue() - 1.0).abs() < 0.05);
+        assert!((aov.depth[8][8].red() - 0.2).abs() < 0.01);
+        assert!(aov.color[8][8].luminance() > 0.0);
+        // The corners miss the sphere.
+        assert_eq!(aov.normal[0][0], image::Pixel::default());
+        assert_eq!(aov.depth[0][0], image::Pixel(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_render_component() {
+        let camera = Camera::new(
+            Point3::zero(),
+            Point3(0.0, 0.0, -1.0),
+            Vect3(0.0, 1.0, 0.0),
+            1.0,
+            f32::INFINITY,
+            (4.0, 2.0),
+        );
+        let sphere = |x: f32, material: Box<dyn Material>| Object {
+            surface: Box::new(Sphere {
+                center: Point3(x, 0.0, -2.0),
+                radius: 0.8,
+            }),
+            material,
+        };
+        let scene = Scene::from_objects(vec![
+            sphere(-1.0, Box::new(Lambertian::new(Color(0.5, 0.5, 0.5)))),
+            sphere(1.0, Box::new(Metal::new(Color(0.8, 0.8, 0.8), 0.0))),
+        ]);
+        let render = |component: Component| {
+            let image = render_component(&scene, &camera, 32, 16, 4, 8, component, |_| ());
+            // The centers of the matte (left) and mirror (right) spheres.
+            (image[8][12].luminance(), image[8][20].luminance())
+        };
+
+        let (matte, mirror) = render(Component::Beauty);
+        assert!(matte > 0.0 && mirror > 0.0);
+        let (matte, mirror) = render(Component::SpecularOnly);
+        assert_eq!(matte, 0.0);
+        assert!(mirror > 0.0);
+        let (matte, mirror) = render(Component::DiffuseOnly);
+        assert!(matte > 0.0);
+        assert_eq!(mirror, 0.0);
+    }
+
+    #[test]
+    fn test_stratified_pixel_offsets() {
+        for _ in 0..10 {
+            let mut quadrants: Vec<(bool, bool)> = pixel_offsets(4)
+                .into_iter()
+                .map(|(x, y)| (x >= 0.5, y >= 0.5))
+                .collect();
+            quadrants.sort();
+            assert_eq!(
+                quadrants,
+                vec![(false, false), (false, true), (true, false), (true, true)]
+            );
+        }
+        let offsets = pixel_offsets(3);
+        assert_eq!(offsets.len(), 3);
+        assert!(offsets
+            .iter()
+            .all(|(x, y)| (0.0..1.0).contains(x) && (0.0..1.0).contains(y)));
+    }
+
+    #[test]
+    fn test_estimate_samples_for_noise() {
+        let (camera, scene) = get_scene(2.0, "small").unwrap();
+        let coarse = estimate_samples_for_noise(&scene, &camera, 8, 4, 4, 0.1);
+        let fine = estimate_samples_for_noise(&scene, &camera, 8, 4, 4, 0.01);
+        assert!(coarse >= 1);
+        assert!(fine > coarse);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_render_into_size_mismatch() {
+        let (camera, scene) = get_scene(2.0, "small").unwrap();
+        let mut image = Image::new(4, 4);
+        render_into(&mut image, &scene, &camera, 1, 4, |_| ());
+    }
+
+    #[test]
+    fn test_scene_registry() {
+        let scenes = list_scenes();
+        assert!(scenes.contains(&"small"));
+        assert!(scenes.contains(&"large"));
+        assert!(get_scene(2.0, "small").is_ok());
+        assert_eq!(
+            get_scene(2.0, "missing").err(),
+            Some(SceneError::Unknown(String::from("missing")))
+        );
+        assert!(matches!(
+            load_scene(2.0, "missing.json"),
+            Err(SceneError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_render_callback_counts_rows() {
+        let (camera, scene) = get_scene(2.0, "small").unwrap();
+        let mut progress = vec![];
+        let image = render(&scene, &camera, 16, 8, 1, 1, 4, 0.0, None, |p: Progress| {
+            progress.push(p)
+        });
+
+        assert_eq!(image.width(), 16);
+        assert_eq!(image.height(), 8);
+        assert_eq!(image.iter().flatten().count(), 16 * 8);
+        let expected: Vec<_> = (1..=8)
+            .map(|rows_done| Progress {
+                rows_done,
+                total_rows: 8,
+            })
+            .collect();
+        assert_eq!(progress, expected);
+        assert_eq!(progress.last().unwrap().fraction(), 1.0);
+    }
+
+    #[test]
+    fn test_render_single_thread() {
+        let (camera, _) = get_scene(2.0, "small").unwrap();
+        let scene =
+            Scene::from_objects(vec![]).with_background(Background::Solid(Color(0.2, 0.4, 0.6)));
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let mut rows = vec![];
+        let single = pool.install(|| {
+            render(&scene, &camera, 16, 8, 4, 1, 4, 0.0, None, |p: Progress| {
+                rows.push(p.rows_done)
+            })
+        });
+
+        assert_eq!(rows, (1..=8).collect::<Vec<_>>());
+        assert_eq!(
+            single,
+            render(&scene, &camera, 16, 8, 4, 1, 4, 0.0, None, |_| {})
+        );
+        assert!(single
+            .iter()
+            .flatten()
+            .all(|pixel| *pixel == image::Pixel(0.2, 0.4, 0.6)));
+    }
+
+    #[test]
+    fn test_render_tiles() {
+        // Tiles which do not evenly divide the image.
+        let mut image = Image::new(8, 5);
+        let mut progress = vec![];
+        render_tiles(
+            &mut image,
+            3,
+            None,
+            |p: Progress| progress.push(p.rows_done),
+            |x, y| image::Pixel(x as f32, y as f32, 0.0),
+        );
+        let expected = Image::from_fn(8, 5, |x, row| image::Pixel(x as f32, (4 - row) as f32, 0.0));
+        assert_eq!(image, expected);
+        assert_eq!(progress, (1..=5).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_render_tiles_match_rows() {
+        let (camera, scene) = get_scene(2.0, "small").unwrap();
+        let (width, height) = (40, 20);
+        let tracing = Tracing {
+            seed: Some(42),
+            ..Tracing::new(4)
+        };
+
+        // Seeded pixels render the same, row by row or in parallel tiles.
+        let rows = Image::from_fn(width, height, |x, row| {
+            let pixel = (x, height - 1 - row);
+            render_pixel(&scene, &camera, pixel, (width, height), 4, tracing)
+        });
+        let mut tiles = Image::new(width, height);
+        render_into_with(&mut tiles, &scene, &camera, 4, tracing, None, |_| ());
+        assert_eq!(tiles, rows);
+
+        let mut unseeded = Image::new(width, height);
+        let tracing = Tracing::new(4);
+        render_into_with(&mut unseeded, &scene, &camera, 4, tracing, None, |_| ());
+        assert_ne!(unseeded, rows);
+    }
+
+    #[test]
+    fn test_render_cancel() {
+        let (camera, _) = get_scene(2.0, "small").unwrap();
+        let background = image::Pixel(0.2, 0.4, 0.6);
+        let scene =
+            Scene::from_objects(vec![]).with_background(Background::Solid(Color(0.2, 0.4, 0.6)));
+
+        // Cancelled before the first row.
+        let cancel = AtomicBool::new(true);
+        let mut rows = 0;
+        let image = render(&scene, &camera, 16, 8, 1, 1, 4, 0.0, Some(&cancel), |_| {
+            rows += 1
+        });
+        assert_eq!(rows, 0);
+        assert_eq!(image, Image::new(16, 8));
+
+        // Cancelled after the third row (on a single thread, so that no other
+        // tiles are in flight when the flag is set). The rows of the first
+        // tiles are all rendered, but the remaining tiles are skipped.
+        let cancel = AtomicBool::new(false);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let (width, height) = (4 * TILE_SIZE, 2 * TILE_SIZE);
+        let image = pool.install(|| {
+            render(
+                &scene,
+                &camera,
+                width,
+                height,
+                1,
+                1,
+                4,
+                0.0,
+                Some(&cancel),
+                |p| {
+                    if p.rows_done == 3 {
+                        cancel.store(true, Ordering::Relaxed);
+                    }
+                },
+            )
+        });
+        let rendered = image
+            .iter()
+            .filter(|row| row.iter().all(|pixel| *pixel == background))
+            .count();
+        let skipped = image
+            .iter()
+            .filter(|row| row.iter().all(|pixel| *pixel == image::Pixel::default()))
+            .count();
+        assert_eq!(rendered, TILE_SIZE);
+        assert_eq!(skipped, TILE_SIZE);
+    }
+
+    #[test]
+    fn test_render_path_samples_share_position() {
+        // A material recording the direction of every ray hitting it.
+        struct Recording(Mutex<Vec<Vect3>>);
+        impl Material for Recording {
+            fn scatter_at(&self, ray: &types::Ray, _: &Intersection) -> Vec<(types::Ray, Color)> {
+                self.0.lock().unwrap().push(ray.direction());
+                vec![]
+            }
+        }
+        let camera = Camera::new(
+            Point3::zero(),
+            Point3(0.0, 0.0, -1.0),
+            Vect3(0.0, 1.0, 0.0),
+            1.0,
+            f32::INFINITY,
+            (2.0, 2.0),
+        );
+        // A sphere surrounding the camera, hit by every primary ray.
+        let mut scene = Scene::from_objects(vec![Object {
+            surface: Box::new(Sphere {
+                center: Point3::zero(),
+                radius: 10.0,
+            }),
+            material: Box::new(Recording(Mutex::new(vec![]))),
+        }]);
+
+        render(&scene, &camera, 2, 2, 1, 5, 4, 0.0, None, |_| ());
+        let material = scene.objects_mut()[0].material.downcast_mut::<Recording>();
+        let directions = material.unwrap().0.get_mut().unwrap().clone();
+        assert_eq!(directions.len(), 2 * 2 * 5);
+        for direction in &directions {
+            let count = directions
+                .iter()
+                .filter(|other| *other == direction)
+                .count();
+            assert_eq!(count, 5);
+        }
+    }
+
+    #[test]
+    fn test_write_png() -> Result<(), io::Error> {
+        let mut image = Image::new(2, 2);
+        image[0][0] = image::Pixel(1.0, 0.0, 0.0);
+        image[0][1] = image::Pixel(0.0, 1.0, 0.0);
+        image[1][0] = image::Pixel(0.0, 0.0, 1.0);
+        image[1][1] = image::Pixel(2.0, 0.25, -1.0);
+        let mut buffer = vec![];
+        let mut rows = 0;
+        write_png(&mut buffer, &image, 1.0, ToneMap::None, |_: usize| {
+            rows += 1
+        })?;
+        assert_eq!(rows, 2);
+
+        let decoder = png::Decoder::new(&buffer[..]);
+        let mut reader = decoder.read_info()?;
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels)?;
+        assert_eq!((info.width, info.height), (2, 2));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        assert_eq!(
+            pixels[..info.buffer_size()],
+            [255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 64, 0]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_ppm_binary() -> Result<(), io::Error> {
+        let mut image = Image::new(3, 2);
+        image[0][0] = image::Pixel(1.0, 0.5, 2.0);
+        let mut buffer = vec![];
+        let mut rows = 0;
+        write_ppm_binary(&mut buffer, &image, 1.0, ToneMap::None, |_: usize| {
+            rows += 1
+        })?;
+
+        let header = b"P6\n3 2\n255\n";
+        assert_eq!(rows, 2);
+        assert_eq!(&buffer[..header.len()], header);
+        assert_eq!(buffer.len() - header.len(), 3 * 2 * 3);
+        assert_eq!(buffer[header.len()..header.len() + 3], [255, 128, 255]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_image_format() {
+        assert_eq!("pgm".parse(), Ok(ImageFormat::Pgm));
+        assert_eq!("PNG".parse(), Ok(ImageFormat::Png));
+        assert!("jpg".parse::<ImageFormat>().is_err());
+        assert_eq!(ImageFormat::from_path("out.HDR").unwrap(), ImageFormat::Hdr);
+        assert!(ImageFormat::from_path("out").is_err());
+    }
+
+    #[test]
+    fn test_save_image() -> Result<(), io::Error> {
+        let image = Image::new(3, 2);
+        let directory = std::env::temp_dir();
+
+        let error = save_image(&image, directory.join("image.jpg"), 2.2, ToneMap::None);
+        let error = error.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(error.to_string().contains("Unsupported image format"));
+        assert!(!directory.join("image.jpg").exists());
+
+        let path = directory.join(format!("raytrust-{}.ppm", std::process::id()));
+        save_image(&image, &path, 2.2, ToneMap::None)?;
+        let buffer = fs::read(&path)?;
+        fs::remove_file(&path)?;
+        assert!(buffer.starts_with(b"P6\n3 2\n255\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_hdr() -> Result<(), io::Error> {
+        let decode = |rgbe: &[u8]| {
+            if rgbe[3] == 0 {
+                return image::Pixel::default();
+            }
+            let scale = 2.0_f32.powi(rgbe[3] as i32 - 128 - 8);
+            let channel = |value: u8| ((value as f32) + 0.5) * scale;
+            image::Pixel(channel(rgbe[0]), channel(rgbe[1]), channel(rgbe[2]))
+        };
+        let mut image = Image::new(2, 1);
+        image[0][0] = image::Pixel(4.0, 0.5, 0.0);
+        image[0][1] = image::Pixel(0.0, 1e-40, 0.0);
+        let mut buffer = vec![];
+        write_hdr(&mut buffer, &image, |_: usize| ())?;
+
+        let header = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 2\n";
+        assert_eq!(&buffer[..header.len()], header);
+        let payload = &buffer[header.len()..];
+        assert_eq!(payload.len(), 2 * 4);
+        let pixel = decode(&payload[..4]);
+        assert!((pixel.red() - 4.0).abs() < 0.05);
+        assert!((pixel.green() - 0.5).abs() < 0.05);
+        assert!(pixel.blue().abs() < 0.05);
+        assert_eq!(payload[4..], [0, 0, 0, 0]);
+        Ok(())
+    }
 }
@@ -1,15 +1,35 @@
 use clap::Parser;
 use core::result::Result;
-use raytrust::{get_scene, render, write_pgm};
-use std::{fs, io};
+use raytrust::{
+    get_scene, list_scenes, load_scene, render, ColorSpace, ImageFormat, Progress, ToneMap,
+};
+use std::path::Path;
+use std::{fs, io, process};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    /// Output file (PGM format)
+    /// Output file ("-" writes to stdout)
     #[arg(short, long)]
     output: Option<String>,
 
+    /// Output format: pgm, ppm, png or hdr (defaults to the output file
+    /// extension, or PGM for stdout and files without an extension)
+    #[arg(long)]
+    format: Option<ImageFormat>,
+
+    /// Color space the scene colors are given in (srgb or aces-cg)
+    #[arg(long, default_value = "srgb")]
+    working_space: ColorSpace,
+
+    /// Color space of the output image (srgb or aces-cg)
+    #[arg(long, default_value = "srgb")]
+    output_space: ColorSpace,
+
+    /// Tone-mapping operator (none, reinhard or aces)
+    #[arg(long, default_value = "none")]
+    tonemap: ToneMap,
+
     /// Image width
     #[arg(long, default_value_t = 800)]
     width: usize,
@@ -18,52 +38,139 @@ struct Cli {
     #[arg(long, default_value_t = 450)]
     height: usize,
 
-    /// Samples per pixel
-    #[arg(long, default_value_t = 10)]
-    samples: usize,
+    /// Sample positions per pixel (anti-aliasing)
+    #[arg(long, alias = "samples", default_value_t = 10)]
+    aa_samples: usize,
+
+    /// Paths traced per sample position (lighting noise reduction)
+    #[arg(long, default_value_t = 1)]
+    path_samples: usize,
 
     /// Recursion depth
     #[arg(long, default_value_t = 50)]
     depth: usize,
 
+    /// Smallest path throughput to keep tracing (0 traces every path fully)
+    #[arg(long, default_value_t = 0.0)]
+    contribution_cutoff: f32,
+
+    /// Number of rendering threads (0 uses all cores)
+    #[arg(long, default_value_t = 0)]
+    threads: usize,
+
+    /// Gamma correction of the output image (must be positive)
+    #[arg(long, default_value_t = 2.2, value_parser = parse_gamma)]
+    gamma: f32,
+
     /// Rendered scene
     #[arg(long, default_value_t = String::from("small"))]
     scene: String,
+
+    /// JSON scene file to render (instead of a pre-defined scene)
+    #[arg(long)]
+    scene_file: Option<String>,
+
+    /// List the available scenes and exit
+    #[arg(long)]
+    list_scenes: bool,
+}
+
+/// Parse a gamma value, rejecting any non-positive gamma.
+fn parse_gamma(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(gamma) if gamma > 0.0 => Ok(gamma),
+        Ok(_) => Err(String::from("gamma must be positive")),
+        Err(error) => Err(error.to_string()),
+    }
 }
 
 fn main() -> Result<(), io::Error> {
     let cli = Cli::parse();
 
+    // Argument: list scenes (and exit)
+    if cli.list_scenes {
+        for name in list_scenes() {
+            println!("{}", name);
+        }
+        return Ok(());
+    }
+
+    // Argument: scene (before touching the output file)
+    let aspect_ratio = (cli.width as f32) / (cli.height as f32);
+    let (camera, scene) = match &cli.scene_file {
+        Some(path) => load_scene(aspect_ratio, path).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        }),
+        None => get_scene(aspect_ratio, cli.scene.as_str()).unwrap_or_else(|error| {
+            eprintln!("{} (use --list-scenes to list available scenes)", error);
+            process::exit(1);
+        }),
+    };
+
+    // Argument: output format (or guess from the output file name, or PGM)
+    let format = match (cli.format, cli.output.as_deref()) {
+        (Some(format), _) => format,
+        (None, Some("-") | None) => ImageFormat::Pgm,
+        (None, Some(file)) if Path::new(file).extension().is_none() => ImageFormat::Pgm,
+        (None, Some(file)) => ImageFormat::from_path(file).unwrap_or_else(|error| {
+            eprintln!("{} (use --format to choose a format)", error);
+            process::exit(1);
+        }),
+    };
+
     // Argument: output file (or stdout if "-")
-    let mut output: Box<dyn io::Write> = match cli.output {
+    let mut output: Box<dyn io::Write> = match cli.output.as_deref() {
+        Some("-") | None => Box::new(io::stdout()),
         Some(file) => Box::new(
             fs::OpenOptions::new()
                 .write(true)
                 .create(true)
+                .truncate(true)
                 .open(file)
                 .unwrap(),
         ),
-        None => Box::new(io::stdout()),
     };
 
     // Sample image
     let width = cli.width;
     let height = cli.height;
-    let samples = cli.samples;
     let depth = cli.depth;
-    let render_pb = indicatif::ProgressBar::new_spinner().with_message("Rendering image");
-    let render_cb = |row: usize| {
-        render_pb.set_message(format!("Rendered line {}/{}", row, height));
-        render_pb.tick()
-    };
-    let (camera, scene) = get_scene((width as f32) / (height as f32), cli.scene.as_str());
-    let image = render(&scene, &camera, width, height, samples, depth, render_cb);
+    let render_pb = indicatif::ProgressBar::new(height as u64).with_style(
+        indicatif::ProgressStyle::with_template(
+            "{msg} [{bar:40}] {pos}/{len} ({percent}%, ETA {eta})",
+        )
+        .unwrap()
+        .progress_chars("=> "),
+    );
+    render_pb.set_message("Rendering image");
+    let render_cb = |progress: Progress| render_pb.set_position(progress.rows_done as u64);
+    let cutoff = cli.contribution_cutoff;
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(cli.threads)
+        .build()
+        .unwrap();
+    let mut image = pool.install(|| {
+        render(
+            &scene,
+            &camera,
+            width,
+            height,
+            cli.aa_samples,
+            cli.path_samples,
+            depth,
+            cutoff,
+            None,
+            render_cb,
+        )
+    });
     render_pb.finish_with_message(format!("{} lines rendered!", height));
+    image.convert_color_space(cli.working_space, cli.output_space);
 
     // Write to file
     let save_pb = indicatif::ProgressBar::new_spinner().with_message("Saving image");
     let save_cb = |_: usize| save_pb.tick();
-    write_pgm(&mut *output, &image, 2.2, save_cb)?;
+    format.write(&mut *output, &image, cli.gamma, cli.tonemap, save_cb)?;
     save_pb.finish_with_message("Image saved!");
 
     Ok(())
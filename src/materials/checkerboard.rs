@@ -0,0 +1,60 @@
+use super::diffuse::scatter_diffuse;
+use super::{Checker, Color, Material, Texture};
+use crate::surfaces::Intersection;
+use crate::types::Ray;
+
+/// A diffuse material with a procedural, three-dimensional checker pattern.
+#[derive(Debug, Clone, Copy)]
+pub struct Checkerboard {
+    pattern: Checker,
+}
+
+impl Checkerboard {
+    /// Construct a checkerboard material alternating between two colors.
+    ///
+    /// # Arguments
+    ///
+    /// * `even` - color of the cell containing the origin
+    /// * `odd` - color of the neighbouring cells
+    /// * `scale` - number of cells per unit of length
+    pub fn new(even: Color, odd: Color, scale: f32) -> Checkerboard {
+        Checkerboard {
+            pattern: Checker::new(even, odd, scale),
+        }
+    }
+}
+
+impl Material for Checkerboard {
+    fn scatter_at(&self, _ray: &Ray, intersection: &Intersection) -> Vec<(Ray, Color)> {
+        let color = self.pattern.value(intersection.uv(), intersection.point());
+        vec![(scatter_diffuse(intersection), color)]
+    }
+
+    fn diffuse_albedo(&self, intersection: &Intersection) -> Option<Color> {
+        Some(self.pattern.value(intersection.uv(), intersection.point()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::{Point3, Vect3};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_checkerboard_alternates() {
+        let white = Color(1.0, 1.0, 1.0);
+        let black = Color(0.0, 0.0, 0.0);
+        let checkerboard = Checkerboard::new(white, black, 2.0);
+        let attenuation = |point: Point3| {
+            let ray = Ray::new(point + Vect3(0.0, 1.0, 0.0), Vect3(0.0, -1.0, 0.0));
+            let intersection = Intersection::new(&ray, point, Vect3(0.0, 1.0, 0.0));
+            checkerboard.scatter_at(&ray, &intersection)[0].1
+        };
+
+        assert_eq!(attenuation(Point3(0.25, 0.0, 0.25)), white);
+        assert_eq!(attenuation(Point3(0.75, 0.0, 0.25)), black);
+        assert_eq!(attenuation(Point3(1.25, 0.0, 0.25)), white);
+        assert_eq!(attenuation(Point3(-0.25, 0.0, 0.25)), black);
+    }
+}
@@ -1,30 +1,27 @@
 use super::{Color, Material};
+use crate::sampler;
 use crate::surfaces::Intersection;
 use crate::types::{Ray, Vect3};
-use rand::{thread_rng, Rng};
+use rand::Rng;
 use rand_distr::Uniform;
 
-fn refract(incident: Vect3, normal: Vect3, ratio: f32) -> Vect3 {
+/// Refract (or reflect) an incident vector at a surface.
+///
+/// The normal must oppose the incident vector, and `ratio` is the ratio
+/// between the refractive indices on the incident and the far side. The
+/// vector is reflected randomly, with a probability given by Schlick's
+/// approximation of the reflectance, or if it can not be refracted.
+fn scatter_direction(incident: Vect3, normal: Vect3, ratio: f32) -> Vect3 {
     let cos_theta = incident.dot(-normal).min(1.0);
-    if cos_theta < 0.0 {
-        refract(incident, -normal, ratio.recip())
-    } else {
-        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
-        let reflection = incident - 2.0 * incident.dot(normal) * normal;
-        let orthogonal = ratio * (incident + cos_theta * normal);
-        let parallel = -(1.0 - orthogonal.dot(orthogonal)).abs().sqrt() * normal;
-        let refraction = orthogonal + parallel;
-        let reflectance = {
-            // Schlick's approximation
-            let r0 = (1.0 - ratio) / (1.0 + ratio);
-            (r0 * r0) + (1.0 - r0 * r0) * (1.0 - cos_theta).powi(5)
-        };
-        let mut rng = thread_rng();
-        if (ratio * sin_theta > 1.0) || (reflectance > rng.sample(Uniform::new(0.0, 1.0))) {
-            reflection
-        } else {
-            refraction
-        }
+    let reflectance = {
+        // Schlick's approximation
+        let r0 = (1.0 - ratio) / (1.0 + ratio);
+        (r0 * r0) + (1.0 - r0 * r0) * (1.0 - cos_theta).powi(5)
+    };
+    let mut rng = sampler::rng();
+    match incident.refract(normal, ratio) {
+        Some(refraction) if reflectance <= rng.sample(Uniform::new(0.0, 1.0)) => refraction,
+        _ => incident.reflect(normal),
     }
 }
 
@@ -32,6 +29,7 @@ fn refract(incident: Vect3, normal: Vect3, ratio: f32) -> Vect3 {
 #[derive(Debug, Clone, Copy)]
 pub struct Dielectric {
     attenuation: Color,
+    absorption: Option<Color>,
     refraction: f32,
 }
 
@@ -40,53 +38,95 @@ impl Dielectric {
     pub fn new(color: Color, refraction: f32) -> Dielectric {
         Dielectric {
             attenuation: color,
+            absorption: None,
+            refraction,
+        }
+    }
+
+    /// Construct a colored material absorbing light inside the medium.
+    ///
+    /// Light traveling a distance `d` through the medium is attenuated by
+    /// `exp(-absorption * d)` per channel (following the Beer–Lambert law).
+    pub fn colored(refraction: f32, absorption: Color) -> Dielectric {
+        Dielectric {
+            attenuation: Color(1.0, 1.0, 1.0),
+            absorption: Some(absorption),
             refraction,
         }
     }
+
+    /// Compute the attenuation of a ray arriving at an intersection.
+    fn attenuation_at(&self, intersection: &Intersection) -> Color {
+        match self.absorption {
+            // Rays hitting the back face have traveled through the medium.
+            Some(absorption) if !intersection.front_face() => {
+                let distance = intersection.distance();
+                Color(
+                    (-absorption.red() * distance).exp(),
+                    (-absorption.green() * distance).exp(),
+                    (-absorption.blue() * distance).exp(),
+                )
+            }
+            _ => self.attenuation,
+        }
+    }
 }
 
 impl Material for Dielectric {
     fn scatter_at(&self, ray: &Ray, intersection: &Intersection) -> Vec<(Ray, Color)> {
         let normal = intersection.normal();
         let incident = ray.direction();
-        let ratio = self.refraction.recip();
-        let refracted = refract(incident, normal, ratio);
-        vec![(Ray::new(intersection.point(), refracted), self.attenuation)]
+        let ratio = if intersection.front_face() {
+            self.refraction.recip()
+        } else {
+            self.refraction
+        };
+        let refracted = scatter_direction(incident, normal, ratio);
+        let attenuation = self.attenuation_at(intersection);
+        vec![(Ray::new(intersection.point(), refracted), attenuation)]
+    }
+
+    fn is_specular(&self) -> bool {
+        true
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::types::Point3;
     use approx::assert_abs_diff_eq;
     use pretty_assertions::assert_eq;
 
     #[test]
-    fn test_refraction_0_deg() {
-        let incident = Vect3(0.0, 0.0, 1.0);
+    fn test_total_internal_reflection() {
+        let incident = Vect3(0.0, 1.0, 1.0).normalize();
         let normal = Vect3(0.0, 0.0, -1.0);
-        let ratio = 2.0_f32.sqrt();
 
-        let result = refract(incident, normal, ratio);
-        assert_eq!(result, Vect3(0.0, 0.0, 1.0));
+        for _ in 0..10 {
+            let result = scatter_direction(incident, normal, 1.5);
+            assert_abs_diff_eq!(result, Vect3(0.0, 1.0, -1.0).normalize(), epsilon = 0.001);
+        }
     }
 
     #[test]
-    fn test_refraction_45_deg() {
-        let incident = Vect3(0.0, 1.0, 1.0).normalize();
-        let normal = Vect3(0.0, 0.0, -1.0);
-        let ratio_1 = 2.0_f32.sqrt();
-        let ratio_2 = 0.0_f32;
-        let ratio_3 = 0.9_f32;
-        let ratio_4 = 1.0_f32;
+    fn test_colored_absorption() {
+        let glass = Dielectric::colored(1.5, Color(0.5, 1.0, 2.0));
+        let ray = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
+        let attenuation = |distance: f32| {
+            let exit = Intersection::new(&ray, Point3(0.0, 0.0, distance), Vect3(0.0, 0.0, 1.0));
+            glass.scatter_at(&ray, &exit)[0].1
+        };
+        let entry = Intersection::new(&ray, Point3(0.0, 0.0, 1.0), Vect3(0.0, 0.0, -1.0));
 
-        let result_1 = refract(incident, normal, ratio_1);
-        let result_2 = refract(incident, normal, ratio_2);
-        let result_3 = refract(incident, normal, ratio_3);
-        let result_4 = refract(incident, normal, ratio_4);
-        assert_abs_diff_eq!(result_1, Vect3(0.0, 1.0, 0.0), epsilon = 0.001);
-        assert_abs_diff_eq!(result_2, Vect3(0.0, 1.0, -1.0).normalize(), epsilon = 0.001);
-        assert_abs_diff_eq!(result_3, Vect3(0.0, 0.636396, 0.771362), epsilon = 0.001);
-        assert_abs_diff_eq!(result_4, incident, epsilon = 0.001);
+        let short = attenuation(1.0);
+        let long = attenuation(4.0);
+        assert_abs_diff_eq!(short.red(), (-0.5_f32).exp(), epsilon = 1e-5);
+        assert_abs_diff_eq!(short.blue(), (-2.0_f32).exp(), epsilon = 1e-5);
+        assert_abs_diff_eq!(long.green(), (-4.0_f32).exp(), epsilon = 1e-5);
+        assert!(long.red() < short.red());
+        assert!(long.green() < short.green());
+        assert!(long.blue() < short.blue());
+        assert_eq!(glass.scatter_at(&ray, &entry)[0].1, Color(1.0, 1.0, 1.0));
     }
 }
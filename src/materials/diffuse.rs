@@ -1,14 +1,16 @@
-use super::{Color, Material};
+use super::{Color, Material, SolidColor, Texture};
+use crate::sampler;
 use crate::surfaces::Intersection;
-use crate::types::{Point3, Ray, Vect3};
-use rand::{thread_rng, Rng};
-use rand_distr::StandardNormal;
+use crate::types::{Onb, Point3, Ray, Vect3};
+use rand::Rng;
+use rand_distr::{StandardNormal, Uniform};
+use std::f32::consts::PI;
 
 /// Pick a random point on a sphere centered on `origin`.
 ///
 /// See <https://mathworld.wolfram.com/SpherePointPicking.html>.
 fn rand_point_on_sphere(origin: &Point3, radius: f32) -> Point3 {
-    let mut rng = thread_rng();
+    let mut rng = sampler::rng();
     let vec = Vect3(
         rng.sample(StandardNormal),
         rng.sample(StandardNormal),
@@ -22,30 +24,130 @@ fn rand_point_on_sphere(origin: &Point3, radius: f32) -> Point3 {
     }
 }
 
-/// A lambertian diffuse material.
+/// Scatter a ray diffusely (with lambertian reflection) at an intersection.
+///
+/// The scattered direction is sampled from a cosine-weighted distribution
+/// over the hemisphere around the normal, by projecting a uniformly picked
+/// point on the unit disk up onto the hemisphere.
+pub(super) fn scatter_diffuse(intersection: &Intersection) -> Ray {
+    let mut rng = sampler::rng();
+    let r = rng.sample(Uniform::new(0.0_f32, 1.0)).sqrt();
+    let phi = rng.sample(Uniform::new(0.0, 2.0 * PI));
+    let local = Vect3(r * phi.cos(), r * phi.sin(), (1.0 - r * r).sqrt());
+    let direction = Onb::from_w(intersection.normal()).local(local);
+    if direction.dot(intersection.normal()) > 0.0 {
+        Ray::new(intersection.point(), direction)
+    } else {
+        Ray::new(intersection.point(), intersection.normal())
+    }
+}
+
+/// A lambertian diffuse material, colored by a texture.
+///
+/// The texture is a solid color by default. Textures picked at runtime can
+/// be used as a `Box<dyn Texture>`. Note that downcasting a material (see
+/// `Material::downcast_ref`) requires the texture type to match, i.e. only
+/// solid-colored materials are downcast to a plain `Lambertian`.
 #[derive(Debug, Clone, Copy)]
-pub struct Lambertian {
-    attenuation: Color,
+pub struct Lambertian<T = SolidColor> {
+    texture: T,
 }
 
 impl Lambertian {
     /// Construct a colored diffuse material with lambertian reflection.
     pub fn new(color: Color) -> Lambertian {
-        Lambertian { attenuation: color }
+        Lambertian::textured(SolidColor(color))
     }
 }
 
-impl Material for Lambertian {
+impl<T: Texture> Lambertian<T> {
+    /// Construct a textured diffuse material with lambertian reflection.
+    pub fn textured(texture: T) -> Lambertian<T> {
+        Lambertian { texture }
+    }
+}
+
+impl<T: Texture + 'static> Material for Lambertian<T> {
     fn scatter_at(&self, _ray: &Ray, intersection: &Intersection) -> Vec<(Ray, Color)> {
-        let origin = intersection.point();
-        let center = origin + intersection.normal();
-        let direction = rand_point_on_sphere(&center, 1.0) - origin;
-        if direction.norm() > 0.0 {
-            vec![(Ray::new(origin, direction), self.attenuation)]
-        } else {
-            vec![(Ray::new(origin, intersection.normal()), self.attenuation)]
+        let attenuation = self.texture.value(intersection.uv(), intersection.point());
+        vec![(scatter_diffuse(intersection), attenuation)]
+    }
+
+    fn diffuse_albedo(&self, intersection: &Intersection) -> Option<Color> {
+        Some(self.texture.value(intersection.uv(), intersection.point()))
+    }
+}
+
+/// A rough diffuse material, with Oren-Nayar reflection.
+///
+/// Rough surfaces (like clay, or the moon) are modelled as made up of many
+/// small lambertian facets, with the `roughness` being the standard deviation
+/// of the facet angles (in radians). This makes the surface appear flatter
+/// and brighter when viewed from the direction of the light. At zero
+/// roughness, this is the same as a lambertian material.
+///
+/// See <https://en.wikipedia.org/wiki/Oren%E2%80%93Nayar_reflectance_model>.
+#[derive(Debug, Clone, Copy)]
+pub struct OrenNayar {
+    attenuation: Color,
+    a: f32,
+    b: f32,
+}
+
+impl OrenNayar {
+    /// Construct a colored diffuse material with Oren-Nayar reflection.
+    pub fn new(color: Color, roughness: f32) -> OrenNayar {
+        let sigma2 = roughness * roughness;
+        OrenNayar {
+            attenuation: color,
+            a: 1.0 - 0.5 * sigma2 / (sigma2 + 0.33),
+            b: 0.45 * sigma2 / (sigma2 + 0.09),
         }
     }
+
+    /// Compute the weight of the reflection of a ray relative to lambertian
+    /// reflection, given the incoming and outgoing directions.
+    fn weight(&self, incoming: Vect3, outgoing: Vect3, normal: Vect3) -> f32 {
+        let cos_in = incoming.dot(normal).clamp(0.0, 1.0);
+        let cos_out = outgoing.dot(normal).clamp(0.0, 1.0);
+        let tangent_in = incoming - normal * cos_in;
+        let tangent_out = outgoing - normal * cos_out;
+        let norms = tangent_in.norm() * tangent_out.norm();
+        let cos_phi = if norms > 0.0 {
+            (tangent_in.dot(tangent_out) / norms).max(0.0)
+        } else {
+            0.0
+        };
+        let (sin_in, sin_out) = (
+            (1.0 - cos_in * cos_in).sqrt(),
+            (1.0 - cos_out * cos_out).sqrt(),
+        );
+        // sin(alpha) * tan(beta), with alpha the largest and beta the smallest angle
+        let sin_tan = if cos_in < cos_out {
+            sin_in * sin_out / cos_out
+        } else if cos_in > 0.0 {
+            sin_in * sin_out / cos_in
+        } else {
+            0.0
+        };
+        self.a + self.b * cos_phi * sin_tan
+    }
+}
+
+impl Material for OrenNayar {
+    fn scatter_at(&self, ray: &Ray, intersection: &Intersection) -> Vec<(Ray, Color)> {
+        let scattered = scatter_diffuse(intersection);
+        let weight = self.weight(
+            -ray.direction(),
+            scattered.direction(),
+            intersection.normal(),
+        );
+        vec![(scattered, self.attenuation * weight)]
+    }
+
+    fn diffuse_albedo(&self, _intersection: &Intersection) -> Option<Color> {
+        Some(self.attenuation * self.a)
+    }
 }
 
 /// A hemispherical diffuse material.
@@ -71,17 +173,23 @@ impl Material for Hemispherical {
             vec![(Ray::new(origin, -direction), self.attenuation)]
         }
     }
+
+    fn diffuse_albedo(&self, _intersection: &Intersection) -> Option<Color> {
+        Some(self.attenuation)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::materials::Checker;
+    use crate::surfaces::{Sphere, Surface};
     use pretty_assertions::assert_eq;
 
     #[test]
     fn test_lambertian_reflects_outward() {
         let ray = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
-        let intersection = Intersection::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
+        let intersection = Intersection::new(&ray, Point3::zero(), Vect3(0.0, 0.0, 1.0));
         let lambertian = Lambertian::new(Color(1.0, 1.0, 1.0));
         let scatters = lambertian.scatter_at(&ray, &intersection);
 
@@ -91,10 +199,73 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_lambertian_cosine_weighted() {
+        let normal = Vect3(1.0, 2.0, -2.0) / 3.0;
+        let ray = Ray::new(Point3::zero(), -normal);
+        let intersection = Intersection::new(&ray, Point3::zero(), normal);
+        let lambertian = Lambertian::new(Color(1.0, 1.0, 1.0));
+        let directions: Vec<_> = (0..20000)
+            .map(|_| lambertian.scatter_at(&ray, &intersection)[0].0.direction())
+            .collect();
+        let count = directions.len() as f32;
+
+        // For a cosine distribution, E[cos θ] = 2/3 and E[cos² θ] = 1/2.
+        let mean = directions
+            .iter()
+            .fold(Vect3::zero(), |acc, direction| acc + *direction)
+            / count;
+        let mean_cos_squared = directions
+            .iter()
+            .map(|direction| direction.dot(normal).powi(2))
+            .sum::<f32>()
+            / count;
+        assert!((mean.dot(normal) - 2.0 / 3.0).abs() < 0.02);
+        assert!((mean - mean.dot(normal) * normal).norm() < 0.02);
+        assert!((mean_cos_squared - 0.5).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_lambertian_checker_texture() {
+        let white = Color(1.0, 1.0, 1.0);
+        let black = Color(0.0, 0.0, 0.0);
+        let lambertian = Lambertian::textured(Checker::new(white, black, 1.0));
+        let sphere = Sphere {
+            center: Point3::zero(),
+            radius: 1.0,
+        };
+        let attenuation = |origin: Point3| {
+            let ray = Ray::new(origin, Point3::zero() - origin);
+            let intersection = sphere.intersected_by(&ray, 0.0..f32::INFINITY)[0];
+            lambertian.scatter_at(&ray, &intersection)[0].1
+        };
+
+        assert_eq!(attenuation(Point3(0.1, 0.1, 5.0)), white);
+        assert_eq!(attenuation(Point3(-0.5, 0.1, 5.0)), black);
+    }
+
+    #[test]
+    fn test_lambertian_boxed_texture() {
+        let white = Color(1.0, 1.0, 1.0);
+        let black = Color(0.0, 0.0, 0.0);
+        let textures: Vec<Box<dyn Texture>> = vec![
+            Box::new(SolidColor(white)),
+            Box::new(Checker::new(white, black, 1.0)),
+        ];
+        let ray = Ray::new(Point3(-0.5, 1.0, 0.5), Vect3(0.0, -1.0, 0.0));
+        let intersection = Intersection::new(&ray, Point3(-0.5, 0.0, 0.5), Vect3(0.0, 1.0, 0.0));
+        let attenuations: Vec<_> = textures
+            .into_iter()
+            .map(|texture| Lambertian::textured(texture).scatter_at(&ray, &intersection)[0].1)
+            .collect();
+
+        assert_eq!(attenuations, vec![white, black]);
+    }
+
     #[test]
     fn test_hemispherical_reflects_outward() {
         let ray = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
-        let intersection = Intersection::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
+        let intersection = Intersection::new(&ray, Point3::zero(), Vect3(0.0, 0.0, 1.0));
         let hemispherical = Hemispherical::new(Color(1.0, 1.0, 1.0));
         let scatters = hemispherical.scatter_at(&ray, &intersection);
 
@@ -103,4 +274,34 @@ mod test {
             assert!(reflection.direction().dot(intersection.normal()) > 0.0);
         }
     }
+
+    #[test]
+    fn test_oren_nayar_smooth_is_lambertian() {
+        let color = Color(0.9, 0.6, 0.3);
+        let normal = Vect3(0.0, 0.6, 0.8);
+        let ray = Ray::new(Point3(1.0, 1.0, 1.0), Vect3(-1.0, -0.5, -1.0));
+        let intersection = Intersection::new(&ray, Point3::zero(), normal);
+        let (_, expected) = Lambertian::new(color).scatter_at(&ray, &intersection)[0];
+
+        let smooth = OrenNayar::new(color, 0.0);
+        for _ in 0..100 {
+            let (reflection, attenuation) = smooth.scatter_at(&ray, &intersection)[0];
+            assert!(reflection.direction().dot(intersection.normal()) > 0.0);
+            assert_eq!(attenuation, expected);
+        }
+    }
+
+    #[test]
+    fn test_oren_nayar_retroreflection() {
+        let color = Color(1.0, 1.0, 1.0);
+        let rough = OrenNayar::new(color, 0.5);
+        let normal = Vect3(0.0, 0.0, 1.0);
+        let incoming = Vect3(1.0, 0.0, 1.0).normalize();
+
+        // Rough surfaces reflect more light back towards the light source.
+        let back = rough.weight(incoming, incoming, normal);
+        let forward = rough.weight(incoming, Vect3(-1.0, 0.0, 1.0).normalize(), normal);
+        assert!(back > forward);
+        assert!(forward < 1.0);
+    }
 }
@@ -0,0 +1,53 @@
+use super::{Color, Material};
+use crate::sampler;
+use crate::surfaces::Intersection;
+use crate::types::{Ray, Vect3};
+use rand::Rng;
+use rand_distr::UnitSphere;
+
+/// An isotropic material, scattering light uniformly in all directions.
+///
+/// This is mostly useful for volumes, such as smoke or fog.
+#[derive(Debug, Clone, Copy)]
+pub struct Isotropic {
+    albedo: Color,
+}
+
+impl Isotropic {
+    /// Construct an isotropic material with a given albedo.
+    pub fn new(albedo: Color) -> Isotropic {
+        Isotropic { albedo }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter_at(&self, _ray: &Ray, intersection: &Intersection) -> Vec<(Ray, Color)> {
+        let [x, y, z]: [f32; 3] = sampler::rng().sample(UnitSphere);
+        let direction = Vect3(x, y, z);
+        vec![(Ray::new(intersection.point(), direction), self.albedo)]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::Point3;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_scatters_in_all_directions() {
+        let isotropic = Isotropic::new(Color(0.5, 0.5, 0.5));
+        let ray = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
+        let intersection = Intersection::new(&ray, Point3(0.0, 0.0, 1.0), Vect3(0.0, 0.0, -1.0));
+        let directions: Vec<Vect3> = (0..1000)
+            .flat_map(|_| isotropic.scatter_at(&ray, &intersection))
+            .map(|(scattered, _)| scattered.direction())
+            .collect();
+
+        for direction in &directions {
+            assert_abs_diff_eq!(direction.norm(), 1.0, epsilon = 1e-4);
+        }
+        assert!(directions.iter().any(|direction| direction.z() < 0.0));
+        assert!(directions.iter().any(|direction| direction.z() > 0.0));
+    }
+}
@@ -0,0 +1,49 @@
+use super::{Color, Material};
+use crate::surfaces::Intersection;
+use crate::types::Ray;
+
+/// An emissive material that diffusely emits light.
+#[derive(Debug, Clone, Copy)]
+pub struct DiffuseLight {
+    emission: Color,
+}
+
+impl DiffuseLight {
+    /// Construct a light emitting a given color, scaled by an intensity.
+    pub fn new(color: Color, intensity: f32) -> DiffuseLight {
+        DiffuseLight {
+            emission: Color(
+                color.red() * intensity,
+                color.green() * intensity,
+                color.blue() * intensity,
+            ),
+        }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter_at(&self, _ray: &Ray, _intersection: &Intersection) -> Vec<(Ray, Color)> {
+        vec![]
+    }
+
+    fn emitted(&self) -> Color {
+        self.emission
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::{Point3, Vect3};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_diffuse_light_emits_without_scattering() {
+        let ray = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
+        let intersection = Intersection::new(&ray, Point3::zero(), Vect3(0.0, 0.0, -1.0));
+        let light = DiffuseLight::new(Color(1.0, 0.5, 0.25), 4.0);
+
+        assert!(light.scatter_at(&ray, &intersection).is_empty());
+        assert_eq!(light.emitted(), Color(4.0, 2.0, 1.0));
+    }
+}
@@ -0,0 +1,118 @@
+use super::{Color, Lobe, Material};
+use crate::sampler;
+use crate::surfaces::Intersection;
+use crate::types::Ray;
+use rand::Rng;
+
+/// A material randomly scattering like one of two other materials.
+///
+/// Each scattering picks the first material with probability `ratio`, and
+/// the second material otherwise. Averaged over many samples, this blends
+/// the two materials (e.g. a mostly diffuse surface with some reflection).
+pub struct Mix {
+    first: Box<dyn Material>,
+    second: Box<dyn Material>,
+    ratio: f32,
+}
+
+impl Mix {
+    /// Construct a material mixing two materials.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - the material picked with probability `ratio`
+    /// * `second` - the material picked otherwise
+    /// * `ratio` - the probability of picking the first material
+    pub fn new(first: Box<dyn Material>, second: Box<dyn Material>, ratio: f32) -> Mix {
+        Mix {
+            first,
+            second,
+            ratio,
+        }
+    }
+
+    /// Get the probability of picking the first material.
+    pub fn ratio(&self) -> f32 {
+        self.ratio
+    }
+}
+
+impl Material for Mix {
+    fn scatter_at(&self, ray: &Ray, intersection: &Intersection) -> Vec<(Ray, Color)> {
+        if sampler::rng().gen::<f32>() < self.ratio {
+            self.first.scatter_at(ray, intersection)
+        } else {
+            self.second.scatter_at(ray, intersection)
+        }
+    }
+
+    fn scatter_lobes(&self, ray: &Ray, intersection: &Intersection) -> Vec<(Ray, Color, Lobe)> {
+        if sampler::rng().gen::<f32>() < self.ratio {
+            self.first.scatter_lobes(ray, intersection)
+        } else {
+            self.second.scatter_lobes(ray, intersection)
+        }
+    }
+
+    fn emitted(&self) -> Color {
+        self.second.emitted().lerp(self.first.emitted(), self.ratio)
+    }
+
+    fn is_specular(&self) -> bool {
+        self.first.is_specular() && self.second.is_specular()
+    }
+
+    fn diffuse_albedo(&self, intersection: &Intersection) -> Option<Color> {
+        let first = self.first.diffuse_albedo(intersection);
+        let second = self.second.diffuse_albedo(intersection);
+        if first.is_none() && second.is_none() {
+            return None;
+        }
+        Some(
+            second
+                .unwrap_or_default()
+                .lerp(first.unwrap_or_default(), self.ratio),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::materials::{DiffuseLight, Lambertian, Metal};
+    use crate::types::{Point3, Vect3};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_mix_ratio_one() {
+        let metal = || Box::new(Metal::new(Color(0.9, 0.8, 0.7), 0.0));
+        let mix = Mix::new(
+            metal(),
+            Box::new(Lambertian::new(Color(0.1, 0.2, 0.3))),
+            1.0,
+        );
+        let ray = Ray::new(Point3(0.0, 1.0, -1.0), Vect3(0.0, -1.0, 1.0));
+        let intersection = Intersection::new(&ray, Point3::zero(), Vect3(0.0, 1.0, 0.0));
+
+        let scatter = |material: &dyn Material| {
+            let scatters = material.scatter_at(&ray, &intersection);
+            scatters
+                .iter()
+                .map(|(ray, color)| (ray.origin(), ray.direction(), *color))
+                .collect::<Vec<_>>()
+        };
+        for _ in 0..100 {
+            assert_eq!(scatter(&mix), scatter(&*metal()));
+        }
+        assert!(!mix.is_specular());
+        assert_eq!(mix.diffuse_albedo(&intersection), Some(Color::default()));
+    }
+
+    #[test]
+    fn test_mix_emitted() {
+        let light = Box::new(DiffuseLight::new(Color(1.0, 0.5, 0.0), 2.0));
+        let mix = Mix::new(light, Box::new(Lambertian::new(Color(1.0, 1.0, 1.0))), 0.25);
+
+        assert_eq!(mix.emitted(), Color(0.5, 0.25, 0.0));
+    }
+}
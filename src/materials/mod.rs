@@ -1,23 +1,102 @@
 /// Materials used to render surfaces.
+mod checkerboard;
 mod dielectric;
 mod diffuse;
+mod isotropic;
+mod light;
+mod mix;
+mod phong;
 mod reflective;
+mod texture;
 
 // Exports.
+pub use checkerboard::Checkerboard;
 pub use dielectric::Dielectric;
-pub use diffuse::{Hemispherical, Lambertian};
+pub use diffuse::{Hemispherical, Lambertian, OrenNayar};
+pub use isotropic::Isotropic;
+pub use light::DiffuseLight;
+pub use mix::Mix;
+pub use phong::Phong;
 pub use reflective::Metal;
+pub use texture::{Checker, SolidColor, Texture};
 
 // Imports.
 use crate::surfaces::Intersection;
 use crate::types::Ray;
+use auto_ops::*;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
 use std::vec::Vec;
 
 /// A color with red/green/blue components.
-#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Color(pub f32, pub f32, pub f32);
 
 impl Color {
+    /// Construct the color of a black-body radiator at a given temperature.
+    ///
+    /// The chromaticity is approximated using a cubic spline fit of the
+    /// Planckian locus, valid between 1667 K and 25000 K (temperatures
+    /// outside this range are clamped). The resulting linear sRGB color
+    /// is normalized so that its brightest channel is `1.0`.
+    ///
+    /// See <https://en.wikipedia.org/wiki/Planckian_locus#Approximation>.
+    pub fn from_temperature(kelvin: f32) -> Color {
+        let t = kelvin.clamp(1667.0, 25000.0);
+        let x = if t <= 4000.0 {
+            -0.266_123_9e9 / t.powi(3) - 0.234_358_9e6 / t.powi(2) + 0.877_695_6e3 / t + 0.179_910
+        } else {
+            -3.025_846_9e9 / t.powi(3) + 2.107_038e6 / t.powi(2) + 0.222_634_7e3 / t + 0.240_390
+        };
+        let y = if t <= 2222.0 {
+            -1.106_381_4 * x.powi(3) - 1.348_110_2 * x.powi(2) + 2.185_558_3 * x - 0.202_196_83
+        } else if t <= 4000.0 {
+            -0.954_947_6 * x.powi(3) - 1.374_185_9 * x.powi(2) + 2.091_37 * x - 0.167_488_67
+        } else {
+            3.081_758 * x.powi(3) - 5.873_387 * x.powi(2) + 3.751_13 * x - 0.370_014_83
+        };
+        // Convert from xyY (with unit luminance) to XYZ, then to linear sRGB.
+        let (cx, cy, cz) = (x / y, 1.0, (1.0 - x - y) / y);
+        let r = (3.2406 * cx - 1.5372 * cy - 0.4986 * cz).max(0.0);
+        let g = (-0.9689 * cx + 1.8758 * cy + 0.0415 * cz).max(0.0);
+        let b = (0.0557 * cx - 0.2040 * cy + 1.0570 * cz).max(0.0);
+        let max = r.max(g).max(b);
+        Color(r / max, g / max, b / max)
+    }
+
+    /// Construct the color of monochromatic light with a given wavelength.
+    ///
+    /// The CIE 1931 color matching functions are approximated using the
+    /// multi-lobe fit by Wyman et al., valid for visible light (wavelengths
+    /// outside 380 nm to 780 nm are black). The resulting linear sRGB color
+    /// is clamped to the sRGB gamut and normalized so that its brightest
+    /// channel is `1.0`.
+    ///
+    /// See <https://jcgt.org/published/0002/02/01/>.
+    pub fn from_wavelength(nm: f32) -> Color {
+        if !(380.0..=780.0).contains(&nm) {
+            return Color::default();
+        }
+        let lobe = |mean: f32, below: f32, above: f32| {
+            let t = (nm - mean) / if nm < mean { below } else { above };
+            (-0.5 * t * t).exp()
+        };
+        let cx = 1.056 * lobe(599.8, 37.9, 31.0) + 0.362 * lobe(442.0, 16.0, 26.7)
+            - 0.065 * lobe(501.1, 20.4, 26.2);
+        let cy = 0.821 * lobe(568.8, 46.9, 40.5) + 0.286 * lobe(530.9, 16.3, 31.1);
+        let cz = 1.217 * lobe(437.0, 11.8, 36.0) + 0.681 * lobe(459.0, 26.0, 13.8);
+        // Convert from XYZ to linear sRGB.
+        let r = (3.2406 * cx - 1.5372 * cy - 0.4986 * cz).max(0.0);
+        let g = (-0.9689 * cx + 1.8758 * cy + 0.0415 * cz).max(0.0);
+        let b = (0.0557 * cx - 0.2040 * cy + 1.0570 * cz).max(0.0);
+        let max = r.max(g).max(b);
+        if max > 0.0 {
+            Color(r / max, g / max, b / max)
+        } else {
+            Color::default()
+        }
+    }
+
     /// The red channel of the color.
     pub fn red(&self) -> f32 {
         self.0
@@ -32,10 +111,64 @@ impl Color {
     pub fn blue(&self) -> f32 {
         self.2
     }
+
+    /// The relative luminance of the color, using Rec. 709 coefficients.
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.red() + 0.7152 * self.green() + 0.0722 * self.blue()
+    }
+
+    /// Clamp each channel of the color into `[0, 1]`.
+    pub fn clamp(self) -> Color {
+        Color(
+            self.0.clamp(0.0, 1.0),
+            self.1.clamp(0.0, 1.0),
+            self.2.clamp(0.0, 1.0),
+        )
+    }
+
+    /// Linearly interpolate between this color (at `0.0`) and another (at `1.0`).
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        self * (1.0 - t) + other * t
+    }
+}
+
+impl_op_ex!(+|a: &Color, b: &Color| -> Color { Color(a.0 + b.0, a.1 + b.1, a.2 + b.2) });
+impl_op_ex!(*|a: &Color, b: &Color| -> Color { Color(a.0 * b.0, a.1 * b.1, a.2 * b.2) });
+impl_op_ex_commutative!(*|a: &Color, b: &f32| -> Color { Color(a.0 * b, a.1 * b, a.2 * b) });
+
+/// The lobe of a material which scattered a ray.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lobe {
+    /// Diffuse (e.g. lambertian) scattering.
+    Diffuse,
+    /// Specular scattering, i.e. mirror reflection or refraction.
+    Specular,
+}
+
+/// Conversion of any `'static` type to `Any`, to allow downcasting trait
+/// objects to a concrete type.
+pub trait AsAny {
+    /// Get the value as `Any`.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Get the value as mutable `Any`.
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
 }
 
 /// A (possibly reflecting) material.
-pub trait Material {
+///
+/// Materials are shared between rendering threads, so must be `Send + Sync`.
+pub trait Material: AsAny + Send + Sync {
     /// Reflect a ray at an intersection point.
     ///
     /// # Arguments
@@ -43,4 +176,127 @@ pub trait Material {
     /// * `ray` - ray to reflect
     /// * `intersection` - intersection to reflect at
     fn scatter_at(&self, ray: &Ray, intersection: &Intersection) -> Vec<(Ray, Color)>;
+
+    /// Reflect a ray at an intersection point, tagging each scattered ray
+    /// with the lobe which scattered it.
+    ///
+    /// By default, every ray is tagged as specular if the material is (see
+    /// `is_specular`), and as diffuse otherwise. Materials combining several
+    /// lobes should override this.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - ray to reflect
+    /// * `intersection` - intersection to reflect at
+    fn scatter_lobes(&self, ray: &Ray, intersection: &Intersection) -> Vec<(Ray, Color, Lobe)> {
+        let lobe = if self.is_specular() {
+            Lobe::Specular
+        } else {
+            Lobe::Diffuse
+        };
+        self.scatter_at(ray, intersection)
+            .into_iter()
+            .map(|(ray, color)| (ray, color, lobe))
+            .collect()
+    }
+
+    /// Get the light emitted by the material.
+    ///
+    /// Most materials do not emit any light, which is the default.
+    fn emitted(&self) -> Color {
+        Color::default()
+    }
+
+    /// Check whether the material scatters light specularly, i.e. by mirror
+    /// reflection or refraction, rather than diffusely.
+    ///
+    /// Most materials are diffuse, which is the default.
+    fn is_specular(&self) -> bool {
+        false
+    }
+
+    /// Get the albedo of the material at an intersection, if it reflects
+    /// light diffusely (with lambertian reflection).
+    ///
+    /// This is used to compute direct lighting from point lights, which are
+    /// never hit by scattered rays. Most materials do not reflect light from
+    /// point lights, which is the default.
+    ///
+    /// # Arguments
+    ///
+    /// * `intersection` - intersection to get the albedo at
+    fn diffuse_albedo(&self, _intersection: &Intersection) -> Option<Color> {
+        None
+    }
+}
+
+impl dyn Material + '_ {
+    /// Downcast the material to a concrete material type, if possible.
+    pub fn downcast_ref<T: Material + 'static>(&self) -> Option<&T> {
+        self.as_any().downcast_ref::<T>()
+    }
+
+    /// Downcast the material to a mutable concrete material type, if possible.
+    pub fn downcast_mut<T: Material + 'static>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut::<T>()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_color_from_temperature_daylight_is_neutral() {
+        let color = Color::from_temperature(6500.0);
+        assert!((color.red() - color.green()).abs() < 0.1);
+        assert!((color.red() - color.blue()).abs() < 0.1);
+        assert!((color.green() - color.blue()).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_color_from_wavelength() {
+        let red = Color::from_wavelength(650.0);
+        let green = Color::from_wavelength(530.0);
+        let blue = Color::from_wavelength(460.0);
+        assert!(red.red() > red.green() && red.red() > red.blue());
+        assert!(green.green() > green.red() && green.green() > green.blue());
+        assert!(blue.blue() > blue.red() && blue.blue() > blue.green());
+        assert_eq!(Color::from_wavelength(900.0), Color::default());
+    }
+
+    #[test]
+    fn test_color_ops() {
+        let color = Color(0.5, 1.0, 2.0);
+
+        assert_eq!(color + Color(0.5, 0.25, -1.0), Color(1.0, 1.25, 1.0));
+        assert_eq!(color * Color(2.0, 0.5, 0.0), Color(1.0, 0.5, 0.0));
+        assert_eq!(color * 2.0, Color(1.0, 2.0, 4.0));
+        assert_eq!(0.5 * color, Color(0.25, 0.5, 1.0));
+        assert_eq!(color.lerp(Color(1.5, 0.0, 0.0), 0.5), Color(1.0, 0.5, 1.0));
+    }
+
+    #[test]
+    fn test_color_clamp() {
+        assert_eq!(Color(-0.5, 0.5, 1.5).clamp(), Color(0.0, 0.5, 1.0));
+        assert_eq!(Color(0.0, 1.0, 0.25).clamp(), Color(0.0, 1.0, 0.25));
+    }
+
+    #[test]
+    fn test_material_downcast() {
+        let metal: Box<dyn Material> = Box::new(Metal::new(Color(1.0, 1.0, 1.0), 0.5));
+        let lambertian: Box<dyn Material> = Box::new(Lambertian::new(Color(1.0, 1.0, 1.0)));
+
+        assert!(metal.downcast_ref::<Metal>().is_some());
+        assert!(lambertian.downcast_ref::<Metal>().is_none());
+        assert!(lambertian.downcast_ref::<Lambertian>().is_some());
+    }
+
+    #[test]
+    fn test_color_from_temperature_warm_and_cool() {
+        let warm = Color::from_temperature(3200.0);
+        let cool = Color::from_temperature(10000.0);
+        assert!(warm.red() > warm.blue());
+        assert!(cool.blue() > cool.red());
+    }
 }
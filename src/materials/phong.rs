@@ -0,0 +1,146 @@
+use super::diffuse::scatter_diffuse;
+use super::{Color, Lobe, Material};
+use crate::sampler;
+use crate::surfaces::Intersection;
+use crate::types::{Onb, Ray, Vect3};
+use rand::Rng;
+use rand_distr::Uniform;
+use std::f32::consts::PI;
+
+/// Pick a random direction in a Phong lobe around `axis`.
+///
+/// The angle to the axis follows a `cos^shininess` distribution, so the lobe
+/// narrows as `shininess` grows.
+fn rand_direction_in_lobe(axis: &Vect3, shininess: f32) -> Vect3 {
+    let mut rng = sampler::rng();
+    let cos_theta = rng
+        .sample(Uniform::new(0.0_f32, 1.0))
+        .powf((shininess + 1.0).recip());
+    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+    let phi: f32 = rng.sample(Uniform::new(0.0, 2.0 * PI));
+    let onb = Onb::from_w(*axis);
+    onb.local(Vect3(
+        sin_theta * phi.cos(),
+        sin_theta * phi.sin(),
+        cos_theta,
+    ))
+}
+
+/// A glossy material combining diffuse and specular (Phong) reflection.
+///
+/// Each scattering picks either the diffuse or the specular lobe, with a
+/// probability proportional to the luminance of its attenuation, and scales
+/// the attenuation by the reciprocal of that probability. Averaged over many
+/// samples, this gives the sum of both lobes.
+#[derive(Debug, Clone, Copy)]
+pub struct Phong {
+    diffuse: Color,
+    specular: Color,
+    shininess: f32,
+}
+
+impl Phong {
+    /// Construct a Phong material.
+    ///
+    /// # Arguments
+    ///
+    /// * `diffuse` - attenuation of the diffuse reflection
+    /// * `specular` - attenuation of the specular reflection
+    /// * `shininess` - exponent of the specular lobe (higher is glossier)
+    pub fn new(diffuse: Color, specular: Color, shininess: f32) -> Phong {
+        Phong {
+            diffuse,
+            specular,
+            shininess,
+        }
+    }
+
+    /// Get the probability of scattering by the specular lobe.
+    fn specular_probability(&self) -> f32 {
+        let (diffuse, specular) = (self.diffuse.luminance(), self.specular.luminance());
+        if diffuse + specular > 0.0 {
+            specular / (diffuse + specular)
+        } else {
+            0.5
+        }
+    }
+}
+
+impl Material for Phong {
+    fn scatter_at(&self, ray: &Ray, intersection: &Intersection) -> Vec<(Ray, Color)> {
+        self.scatter_lobes(ray, intersection)
+            .into_iter()
+            .map(|(ray, color, _)| (ray, color))
+            .collect()
+    }
+
+    fn scatter_lobes(&self, ray: &Ray, intersection: &Intersection) -> Vec<(Ray, Color, Lobe)> {
+        let probability = self.specular_probability();
+        if sampler::rng().gen::<f32>() >= probability {
+            let attenuation = self.diffuse * (1.0 - probability).recip();
+            return vec![(scatter_diffuse(intersection), attenuation, Lobe::Diffuse)];
+        }
+        // Specular directions below the surface are absorbed.
+        let normal = intersection.normal();
+        let reflection = ray.direction().reflect(normal);
+        let direction = rand_direction_in_lobe(&reflection, self.shininess);
+        if direction.dot(normal) > 0.0 {
+            let attenuation = self.specular * probability.recip();
+            let reflected = Ray::new(intersection.point(), direction);
+            vec![(reflected, attenuation, Lobe::Specular)]
+        } else {
+            vec![]
+        }
+    }
+
+    fn diffuse_albedo(&self, _intersection: &Intersection) -> Option<Color> {
+        Some(self.diffuse)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::Point3;
+
+    #[test]
+    fn test_shininess_narrows_specular_lobe() {
+        let diffuse = Color(1.0, 0.0, 0.0);
+        let specular = Color(0.0, 0.0, 1.0);
+        let ray = Ray::new(Point3(0.0, 1.0, -1.0), Vect3(0.0, -1.0, 1.0));
+        let intersection = Intersection::new(&ray, Point3::zero(), Vect3(0.0, 1.0, 0.0));
+        let mirror = Vect3(0.0, 1.0, 1.0).normalize();
+        let spread = |shininess: f32| {
+            let material = Phong::new(diffuse, specular, shininess);
+            let cosines: Vec<f32> = (0..4000)
+                .flat_map(|_| material.scatter_at(&ray, &intersection))
+                .filter(|(_, attenuation)| attenuation.blue() > 0.0)
+                .map(|(scattered, _)| scattered.direction().normalize().dot(mirror))
+                .collect();
+            cosines.iter().sum::<f32>() / (cosines.len() as f32)
+        };
+
+        let rough = spread(1.0);
+        let glossy = spread(100.0);
+        assert!(glossy > rough);
+        assert!(glossy > 0.95);
+    }
+
+    #[test]
+    fn test_lobes_are_unbiased() {
+        let diffuse = Color(1.0, 0.0, 0.0);
+        let specular = Color(0.0, 0.0, 1.0);
+        // A grazing ray, for which most specular directions are absorbed.
+        let ray = Ray::new(Point3(0.0, 0.1, -1.0), Vect3(0.0, -0.1, 1.0));
+        let intersection = Intersection::new(&ray, Point3::zero(), Vect3(0.0, 1.0, 0.0));
+        let material = Phong::new(diffuse, specular, 1.0);
+        let samples = 20000;
+        let red: f32 = (0..samples)
+            .flat_map(|_| material.scatter_at(&ray, &intersection))
+            .map(|(_, attenuation)| attenuation.red())
+            .sum();
+
+        // The diffuse lobe does not depend on the specular lobe.
+        assert!((red / (samples as f32) - 1.0).abs() < 0.03);
+    }
+}
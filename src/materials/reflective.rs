@@ -1,7 +1,8 @@
 use super::{Color, Material};
+use crate::sampler;
 use crate::surfaces::Intersection;
-use crate::types::{Ray, Vect3};
-use rand::{thread_rng, Rng};
+use crate::types::{Onb, Ray, Vect3};
+use rand::Rng;
 use rand_distr::Uniform;
 use std::f32::consts::PI;
 
@@ -9,16 +10,18 @@ use std::f32::consts::PI;
 ///
 /// See <https://mathworld.wolfram.com/DiskPointPicking.html>.
 fn rand_point_on_disk(normal: &Vect3, radius: f32) -> Vect3 {
-    let mut rng = thread_rng();
+    let mut rng = sampler::rng();
     let r: f32 = rng.sample(Uniform::new_inclusive(0.0, radius));
     let phi: f32 = rng.sample(Uniform::new(0.0, 2.0 * PI));
-    let horiz = Vect3(1.0, 0.0, 0.0);
-    let x = (horiz - normal.project(horiz)).normalize();
-    let y = normal.cross(x);
-    (x * r.sqrt() * phi.cos()) + (y * r.sqrt() * phi.sin())
+    let onb = Onb::from_w(*normal);
+    onb.local(Vect3(r.sqrt() * phi.cos(), r.sqrt() * phi.sin(), 0.0))
 }
 
 /// A reflective metal-like material.
+///
+/// The reflectance follows Schlick's approximation of the Fresnel equations,
+/// using the color of the metal as the reflectance at normal incidence, so
+/// that the reflection turns white at grazing angles.
 #[derive(Debug, Clone, Copy)]
 pub struct Metal {
     attenuation: Color,
@@ -33,18 +36,66 @@ impl Metal {
             pertubation: fuzziness,
         }
     }
+
+    /// Get the fuzziness of the reflection.
+    pub fn fuzziness(&self) -> f32 {
+        self.pertubation
+    }
+
+    /// Change the fuzziness of the reflection.
+    pub fn set_fuzziness(&mut self, fuzziness: f32) {
+        self.pertubation = fuzziness;
+    }
+
+    /// Compute the reflectance for a given incident ray direction.
+    fn reflectance(&self, incident: Vect3, normal: Vect3) -> Color {
+        // Schlick's approximation
+        let cos_theta = incident.dot(-normal).clamp(0.0, 1.0);
+        let white = Color(1.0, 1.0, 1.0);
+        self.attenuation.lerp(white, (1.0 - cos_theta).powi(5))
+    }
 }
 
 impl Material for Metal {
     fn scatter_at(&self, ray: &Ray, intersection: &Intersection) -> Vec<(Ray, Color)> {
-        let normal = intersection.normal();
-        let incident = ray.direction();
-        let reflection = incident - 2.0 * incident.dot(normal) * normal;
+        let reflection = ray.direction().reflect(intersection.normal());
         let direction = reflection + rand_point_on_disk(&reflection, self.pertubation);
         if direction.dot(intersection.normal()) > 0.0 {
-            vec![(Ray::new(intersection.point(), direction), self.attenuation)]
+            let attenuation = self.reflectance(ray.direction(), intersection.normal());
+            vec![(Ray::new(intersection.point(), direction), attenuation)]
         } else {
             vec![]
         }
     }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::Point3;
+
+    #[test]
+    fn test_metal_fresnel() {
+        let metal = Metal::new(Color(0.8, 0.4, 0.2), 0.0);
+        let normal = Vect3(0.0, 0.0, 1.0);
+        let attenuation = |direction: Vect3| {
+            let ray = Ray::new(Point3(0.0, 0.0, 1.0), direction);
+            let intersection = Intersection::new(&ray, Point3::zero(), normal);
+            metal.scatter_at(&ray, &intersection)[0].1
+        };
+
+        let normal_incidence = attenuation(Vect3(0.0, 0.01, -1.0));
+        assert!((normal_incidence.red() - 0.8).abs() < 1e-3);
+        assert!((normal_incidence.green() - 0.4).abs() < 1e-3);
+        assert!((normal_incidence.blue() - 0.2).abs() < 1e-3);
+
+        let grazing = attenuation(Vect3(0.0, 1.0, -0.001));
+        assert!(grazing.red() > 0.99);
+        assert!(grazing.green() > 0.99);
+        assert!(grazing.blue() > 0.99);
+    }
 }
@@ -0,0 +1,89 @@
+use super::Color;
+use crate::types::Point3;
+
+/// A texture, mapping points on a surface to colors.
+pub trait Texture: Send + Sync {
+    /// Get the color of the texture at a point.
+    ///
+    /// # Arguments
+    ///
+    /// * `uv` - texture coordinates of the point
+    /// * `point` - the point itself
+    fn value(&self, uv: (f32, f32), point: Point3) -> Color;
+}
+
+impl<T: Texture + ?Sized> Texture for Box<T> {
+    fn value(&self, uv: (f32, f32), point: Point3) -> Color {
+        (**self).value(uv, point)
+    }
+}
+
+/// A texture with a single, solid color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolidColor(pub Color);
+
+impl Texture for SolidColor {
+    fn value(&self, _uv: (f32, f32), _point: Point3) -> Color {
+        self.0
+    }
+}
+
+/// A procedural, three-dimensional checker pattern texture.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Checker {
+    even: Color,
+    odd: Color,
+    scale: f32,
+}
+
+impl Checker {
+    /// Construct a checker texture alternating between two colors.
+    ///
+    /// # Arguments
+    ///
+    /// * `even` - color of the cell containing the origin
+    /// * `odd` - color of the neighbouring cells
+    /// * `scale` - number of cells per unit of length
+    pub fn new(even: Color, odd: Color, scale: f32) -> Checker {
+        Checker { even, odd, scale }
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, _uv: (f32, f32), point: Point3) -> Color {
+        let cell = (point.x() * self.scale).floor()
+            + (point.y() * self.scale).floor()
+            + (point.z() * self.scale).floor();
+        if (cell as i32).rem_euclid(2) == 0 {
+            self.even
+        } else {
+            self.odd
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_solid_color() {
+        let color = Color(0.1, 0.2, 0.3);
+        let texture = SolidColor(color);
+
+        assert_eq!(texture.value((0.0, 0.0), Point3::zero()), color);
+        assert_eq!(texture.value((0.5, 1.0), Point3(1.0, 2.0, 3.0)), color);
+    }
+
+    #[test]
+    fn test_checker() {
+        let white = Color(1.0, 1.0, 1.0);
+        let black = Color(0.0, 0.0, 0.0);
+        let texture = Checker::new(white, black, 1.0);
+
+        assert_eq!(texture.value((0.0, 0.0), Point3(0.5, 0.5, 0.5)), white);
+        assert_eq!(texture.value((0.0, 0.0), Point3(1.5, 0.5, 0.5)), black);
+        assert_eq!(texture.value((0.0, 0.0), Point3(1.5, 1.5, 0.5)), white);
+    }
+}
@@ -0,0 +1,70 @@
+use rand::rngs::StdRng;
+use rand::{Error, RngCore, SeedableRng};
+use std::cell::RefCell;
+
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// A handle to the random number generator of the current thread.
+///
+/// This is used instead of `rand::thread_rng` for everything sampled while
+/// rendering. Unlike the thread-local generator of `rand`, it can be reseeded
+/// (see `seed_pixel`), which makes seeded renders reproducible regardless of
+/// which thread renders which pixel.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct SamplerRng;
+
+/// Get a handle to the random number generator of the current thread.
+pub(crate) fn rng() -> SamplerRng {
+    SamplerRng
+}
+
+/// Reseed the random number generator of the current thread, before
+/// rendering a pixel of a seeded render.
+///
+/// # Arguments
+///
+/// * `seed` - seed of the whole render
+/// * `pixel` - index of the pixel within the image
+pub(crate) fn seed_pixel(seed: u64, pixel: u64) {
+    let seed = seed ^ pixel.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+impl RngCore for SamplerRng {
+    fn next_u32(&mut self) -> u32 {
+        RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rand::Rng;
+
+    #[test]
+    fn test_seed_pixel() {
+        let sample = |seed: u64, pixel: u64| {
+            seed_pixel(seed, pixel);
+            (0..4).map(|_| rng().gen::<u32>()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(sample(1, 2), sample(1, 2));
+        assert_ne!(sample(1, 2), sample(1, 3));
+        assert_ne!(sample(1, 2), sample(2, 2));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::image::{Image, Pixel};
+use crate::materials::Color;
+use crate::types::{Ray, Vect3};
+use std::f32::consts::{FRAC_PI_6, PI};
+
+/// Angular radius of the sun disc, in radians.
+const SUN_RADIUS: f32 = 0.03;
+
+/// The background of a scene, i.e. the color of rays missing every object.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum Background {
+    /// A single color in every direction.
+    Solid(Color),
+    /// A vertical gradient, from the first color straight down to the second
+    /// color straight up.
+    Gradient(Color, Color),
+    /// A blue-ish sky, fading to white towards the horizon and below.
+    #[default]
+    Sky,
+    /// A sky lit by a sun at a given elevation (in radians) in the `-z`
+    /// direction.
+    ///
+    /// The sky fades from blue at the zenith to a horizon color which turns
+    /// warm orange as the sun sets (below about 30° elevation), to simulate
+    /// sunrise and sunset. The sun itself is drawn as a small bright disc.
+    DynamicSky { sun_elevation: f32 },
+    /// An environment map, as an image in equirectangular projection.
+    ///
+    /// The top row of the image is straight up, and the bottom row straight
+    /// down. The left and right edges of the image are both in the `-x`
+    /// direction, with `+z` a quarter of the image width from the left.
+    Environment(Image),
+}
+
+impl Background {
+    /// Get the background color in the direction of a ray.
+    pub fn color(&self, ray: &Ray) -> Pixel {
+        match self {
+            Background::Solid(color) => Pixel(color.red(), color.green(), color.blue()),
+            Background::Gradient(bottom, top) => {
+                let t = 0.5 * (ray.direction().y() + 1.0);
+                let bottom = Pixel(bottom.red(), bottom.green(), bottom.blue());
+                let top = Pixel(top.red(), top.green(), top.blue());
+                ((1.0 - t) * bottom) + (t * top)
+            }
+            Background::Sky => {
+                Background::Gradient(Color(1.0, 1.0, 1.0), Color(0.5, 0.7, 1.0)).color(ray)
+            }
+            Background::DynamicSky { sun_elevation } => dynamic_sky(ray, *sun_elevation),
+            Background::Environment(image) => {
+                if image.width() == 0 || image.height() == 0 {
+                    return Pixel::default();
+                }
+                let (u, v) = equirectangular_uv(ray);
+                // Wrap around the seam, and clamp at the poles.
+                let x = ((u * image.width() as f32) as usize) % image.width();
+                let y = ((v * image.height() as f32) as usize).min(image.height() - 1);
+                image[y][x]
+            }
+        }
+    }
+}
+
+/// Compute the color of a sky lit by a sun at a given elevation.
+fn dynamic_sky(ray: &Ray, sun_elevation: f32) -> Pixel {
+    let lerp = |a: Pixel, b: Pixel, t: f32| ((1.0 - t) * a) + (t * b);
+    let direction = ray.direction();
+    let sun = Vect3(0.0, sun_elevation.sin(), -sun_elevation.cos());
+    // The sky is warmer the closer the sun is to the horizon.
+    let warmth = 1.0 - (sun_elevation / FRAC_PI_6).clamp(0.0, 1.0);
+    let horizon = lerp(Pixel(0.9, 0.95, 1.0), Pixel(1.0, 0.55, 0.25), warmth);
+    let zenith = Pixel(0.3, 0.5, 1.0);
+    let sky = lerp(horizon, zenith, direction.y().clamp(0.0, 1.0).sqrt());
+    if direction.dot(sun) > SUN_RADIUS.cos() {
+        let disc = lerp(Pixel(1.0, 1.0, 0.95), Pixel(1.0, 0.6, 0.3), warmth);
+        sky + 5.0 * disc
+    } else {
+        sky
+    }
+}
+
+/// Compute the equirectangular texture coordinates in the direction of a ray.
+///
+/// The `u` coordinate is the longitude around the y axis, starting at `-x`,
+/// while `v` is the polar angle, from `0.0` at `+y` to `1.0` at `-y`.
+fn equirectangular_uv(ray: &Ray) -> (f32, f32) {
+    let direction = ray.direction();
+    let theta = direction.y().clamp(-1.0, 1.0).acos();
+    let phi = (-direction.z()).atan2(direction.x()) + PI;
+    (phi / (2.0 * PI), theta / PI)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::{Point3, Vect3};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_gradient() {
+        let background = Background::Gradient(Color(1.0, 0.0, 0.0), Color(0.0, 0.0, 1.0));
+        let color = |direction: Vect3| background.color(&Ray::new(Point3::zero(), direction));
+
+        assert_eq!(color(Vect3(0.0, -1.0, 0.0)), Pixel(1.0, 0.0, 0.0));
+        assert_eq!(color(Vect3(0.0, 1.0, 0.0)), Pixel(0.0, 0.0, 1.0));
+        assert_eq!(color(Vect3(1.0, 0.0, 0.0)), Pixel(0.5, 0.0, 0.5));
+    }
+
+    #[test]
+    fn test_dynamic_sky() {
+        let horizon = |sun_elevation: f32| {
+            let background = Background::DynamicSky { sun_elevation };
+            let color = background.color(&Ray::new(Point3::zero(), Vect3(1.0, 0.0, 0.0)));
+            color.red() / color.blue()
+        };
+        let sun = |sun_elevation: f32| {
+            let background = Background::DynamicSky { sun_elevation };
+            let direction = Vect3(0.0, sun_elevation.sin(), -sun_elevation.cos());
+            background.color(&Ray::new(Point3::zero(), direction))
+        };
+
+        assert!(horizon(0.05) > horizon(1.0));
+        assert!(sun(1.0).luminance() > 1.0);
+        assert!(sun(0.05).red() / sun(0.05).blue() > sun(1.0).red() / sun(1.0).blue());
+    }
+
+    #[test]
+    fn test_environment() {
+        let mut image = Image::new(4, 3);
+        image[0].fill(Pixel(1.0, 0.0, 0.0));
+        image[2].fill(Pixel(0.0, 0.0, 1.0));
+        image[1][1] = Pixel(0.0, 1.0, 0.0);
+        let background = Background::Environment(image);
+        let color = |direction: Vect3| background.color(&Ray::new(Point3::zero(), direction));
+
+        assert_eq!(color(Vect3(0.0, 1.0, 0.0)), Pixel(1.0, 0.0, 0.0));
+        assert_eq!(color(Vect3(0.0, -1.0, 0.0)), Pixel(0.0, 0.0, 1.0));
+        assert_eq!(color(Vect3(0.0, 0.0, 1.0)), Pixel(0.0, 1.0, 0.0));
+        // Directions at (or next to) the seam do not panic.
+        for direction in [
+            Vect3(-1.0, 0.0, 0.0),
+            Vect3(-1.0, 0.0, 1e-7),
+            Vect3(-1.0, 0.0, -1e-7),
+            Vect3(-1e-7, -1.0, 0.0),
+        ] {
+            color(direction);
+        }
+        let empty = Background::Environment(Image::new(0, 0));
+        assert_eq!(
+            empty.color(&Ray::new(Point3::zero(), Vect3(0.0, 1.0, 0.0))),
+            Pixel::default()
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use super::{Background, Object, PointLight, Scene};
+use crate::materials::{Color, Material};
+use crate::surfaces::{Sphere, Surface};
+use crate::types::Point3;
+
+/// A builder, for fluently constructing a scene.
+///
+/// # Example
+///
+/// ```
+/// use raytrust::{Color, Lambertian, Point3, SceneBuilder};
+///
+/// let scene = SceneBuilder::new()
+///     .sphere(Point3(0.0, 0.0, -1.0), 0.5, Lambertian::new(Color(0.1, 0.2, 0.5)))
+///     .sphere(Point3(0.0, -100.5, -1.0), 100.0, Lambertian::new(Color(0.8, 0.8, 0.0)))
+///     .build();
+/// assert_eq!(scene.objects().len(), 2);
+/// ```
+#[derive(Default)]
+pub struct SceneBuilder {
+    objects: Vec<Object>,
+    background: Background,
+    lights: Vec<PointLight>,
+}
+
+impl SceneBuilder {
+    /// Construct a builder for an empty scene.
+    pub fn new() -> SceneBuilder {
+        SceneBuilder::default()
+    }
+
+    /// Add an object with any surface to the scene.
+    pub fn object(
+        mut self,
+        surface: impl Surface + 'static,
+        material: impl Material + 'static,
+    ) -> SceneBuilder {
+        self.objects.push(Object::new(surface, material));
+        self
+    }
+
+    /// Add a sphere to the scene.
+    pub fn sphere(
+        self,
+        center: Point3,
+        radius: f32,
+        material: impl Material + 'static,
+    ) -> SceneBuilder {
+        self.object(Sphere { center, radius }, material)
+    }
+
+    /// Add a point light to the scene.
+    pub fn light(mut self, position: Point3, intensity: Color) -> SceneBuilder {
+        self.lights.push(PointLight {
+            position,
+            intensity,
+        });
+        self
+    }
+
+    /// Change the background of the scene.
+    pub fn background(self, background: Background) -> SceneBuilder {
+        SceneBuilder { background, ..self }
+    }
+
+    /// Construct the scene.
+    pub fn build(self) -> Scene {
+        Scene::from_objects(self.objects)
+            .with_background(self.background)
+            .with_lights(self.lights)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::materials::{Lambertian, Metal};
+    use crate::types::{Ray, Vect3};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_builder_matches_manual() {
+        let manual = Scene::from_objects(vec![
+            Object {
+                surface: Box::new(Sphere {
+                    center: Point3(0.0, 0.0, 2.0),
+                    radius: 1.0,
+                }),
+                material: Box::new(Lambertian::new(Color(1.0, 1.0, 1.0))),
+            },
+            Object {
+                surface: Box::new(Sphere {
+                    center: Point3(0.0, 0.0, 4.0),
+                    radius: 1.0,
+                }),
+                material: Box::new(Metal::new(Color(1.0, 1.0, 1.0), 0.5)),
+            },
+        ]);
+        let built = SceneBuilder::new()
+            .sphere(
+                Point3(0.0, 0.0, 2.0),
+                1.0,
+                Lambertian::new(Color(1.0, 1.0, 1.0)),
+            )
+            .object(
+                Sphere {
+                    center: Point3(0.0, 0.0, 4.0),
+                    radius: 1.0,
+                },
+                Metal::new(Color(1.0, 1.0, 1.0), 0.5),
+            )
+            .build();
+
+        assert_eq!(built.objects.len(), manual.objects.len());
+        for (filter, fuzzy) in [(0.0..f32::INFINITY, false), (3.5..f32::INFINITY, true)] {
+            let ray = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
+            let hit = |scene: &Scene| {
+                let (hit, material) = ray.intersects(scene, filter.clone()).unwrap();
+                (hit, material.downcast_ref::<Metal>().is_some())
+            };
+            assert_eq!(hit(&built), hit(&manual));
+            assert_eq!(hit(&built).1, fuzzy);
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use super::{hit_order, Object};
+use crate::materials::Material;
+use crate::surfaces::{Aabb, Intersection};
+use crate::types::{Point3, Ray};
+use std::cmp::Ordering;
+use std::ops::Range;
+
+/// Largest number of objects stored in a single leaf node.
+const LEAF_SIZE: usize = 2;
+
+/// Sort object indices by the centroid of their bounding boxes along an axis.
+fn sort_by_centroid(boxes: &[Aabb], indices: &mut [usize], axis: usize) {
+    indices.sort_by(|a, b| {
+        let a = boxes[*a].centroid()[axis];
+        let b = boxes[*b].centroid()[axis];
+        a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+    });
+}
+
+/// Strategy used to split the objects of a node when building a hierarchy.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BvhBuildStrategy {
+    /// Split at the median centroid along the longest axis.
+    #[default]
+    Median,
+    /// Split where the surface area heuristic (SAH) estimates the lowest
+    /// traversal cost, considering every split along every axis.
+    Sah,
+}
+
+/// A node in a bounding volume hierarchy.
+#[derive(Debug)]
+enum Node {
+    Leaf(Aabb, Vec<usize>),
+    Split(Aabb, Box<Node>, Box<Node>),
+}
+
+impl Node {
+    /// Build a node containing a set of objects.
+    fn build(boxes: &[Aabb], mut indices: Vec<usize>, strategy: BvhBuildStrategy) -> Node {
+        let bounds = Aabb::surrounding_box(indices.iter().map(|index| boxes[*index]))
+            .expect("BVH nodes must not be empty");
+        if indices.len() <= LEAF_SIZE {
+            return Node::Leaf(bounds, indices);
+        }
+        let split = match strategy {
+            BvhBuildStrategy::Median => Node::split_median(boxes, &mut indices),
+            BvhBuildStrategy::Sah => Node::split_sah(boxes, &mut indices),
+        };
+        let right = indices.split_off(split);
+        Node::Split(
+            bounds,
+            Box::new(Node::build(boxes, indices, strategy)),
+            Box::new(Node::build(boxes, right, strategy)),
+        )
+    }
+
+    /// Sort objects for splitting in two halves at the median centroid along
+    /// the longest axis of their centroids' bounding box.
+    ///
+    /// Returns the index at which to split the sorted objects.
+    fn split_median(boxes: &[Aabb], indices: &mut [usize]) -> usize {
+        let centroids: Vec<Point3> = indices.iter().map(|i| boxes[*i].centroid()).collect();
+        let extent = Aabb::from_points(&centroids);
+        let size = extent.max - extent.min;
+        let axis = if size.x() >= size.y() && size.x() >= size.z() {
+            0
+        } else if size.y() >= size.z() {
+            1
+        } else {
+            2
+        };
+        sort_by_centroid(boxes, indices, axis);
+        indices.len() / 2
+    }
+
+    /// Sort objects for splitting where the surface area heuristic estimates
+    /// the lowest cost, i.e. where the sum of the areas of the two halves
+    /// (weighted by their number of objects) is the smallest.
+    ///
+    /// Returns the index at which to split the sorted objects.
+    fn split_sah(boxes: &[Aabb], indices: &mut [usize]) -> usize {
+        let len = indices.len();
+        let mut best: Option<(f32, usize, usize)> = None;
+        for axis in 0..3 {
+            sort_by_centroid(boxes, indices, axis);
+            // Sweep from both ends, computing the area of every prefix/suffix.
+            let sweep = |order: &mut dyn Iterator<Item = &usize>| {
+                order
+                    .scan(None, |acc: &mut Option<Aabb>, index| {
+                        let bounds = acc.map_or(boxes[*index], |acc| acc.union(&boxes[*index]));
+                        *acc = Some(bounds);
+                        Some(bounds.area())
+                    })
+                    .collect::<Vec<f32>>()
+            };
+            let left = sweep(&mut indices.iter());
+            let right = sweep(&mut indices.iter().rev());
+            for split in 1..len {
+                let cost = left[split - 1] * (split as f32)
+                    + right[len - split - 1] * ((len - split) as f32);
+                if best.is_none_or(|(best, _, _)| cost < best) {
+                    best = Some((cost, axis, split));
+                }
+            }
+        }
+        match best {
+            Some((_, axis, split)) => {
+                sort_by_centroid(boxes, indices, axis);
+                split
+            }
+            None => Node::split_median(boxes, indices),
+        }
+    }
+
+    /// Find the closest intersection with any object in this node.
+    ///
+    /// The closest intersection found so far (along with its object index)
+    /// is given in `closest`, and used to prune nodes that are further away.
+    fn intersects(
+        &self,
+        objects: &[Object],
+        ray: &Ray,
+        filter: &Range<f32>,
+        closest: &mut Option<(Intersection, usize)>,
+        visits: &mut usize,
+    ) {
+        *visits += 1;
+        let limit = closest.map_or(filter.end, |(hit, _)| {
+            (hit.distance_squared() / ray.direction().norm_squared()).sqrt()
+        });
+        match self {
+            Node::Leaf(bounds, indices) => {
+                if !bounds.hit(ray, filter.start..limit) {
+                    return;
+                }
+                for index in indices {
+                    let hits = objects[*index].surface.intersected_by(ray, filter.clone());
+                    for hit in hits {
+                        let candidate = (hit, *index);
+                        let closer = match closest {
+                            Some(current) => hit_order(&candidate, current).is_lt(),
+                            None => true,
+                        };
+                        if closer {
+                            *closest = Some(candidate);
+                        }
+                    }
+                }
+            }
+            Node::Split(bounds, left, right) => {
+                if bounds.hit(ray, filter.start..limit) {
+                    left.intersects(objects, ray, filter, closest, visits);
+                    right.intersects(objects, ray, filter, closest, visits);
+                }
+            }
+        }
+    }
+}
+
+/// A bounding volume hierarchy over the objects of a scene.
+///
+/// The hierarchy refers to objects by index, so must be rebuilt whenever
+/// the surfaces of the scene change.
+#[derive(Debug)]
+pub struct Bvh {
+    root: Option<Node>,
+}
+
+impl Bvh {
+    /// Build a bounding volume hierarchy for a list of objects.
+    pub fn new(objects: &[Object]) -> Bvh {
+        Bvh::with_strategy(objects, BvhBuildStrategy::default())
+    }
+
+    /// Build a bounding volume hierarchy using a specific split strategy.
+    pub fn with_strategy(objects: &[Object], strategy: BvhBuildStrategy) -> Bvh {
+        let boxes: Vec<Aabb> = objects
+            .iter()
+            .map(|object| object.surface.bounding_box())
+            .collect();
+        let root = if objects.is_empty() {
+            None
+        } else {
+            Some(Node::build(&boxes, (0..objects.len()).collect(), strategy))
+        };
+        Bvh { root }
+    }
+
+    /// Find the closest intersection between a ray and any of the objects.
+    ///
+    /// Intersections at equal distance are resolved in the same way as when
+    /// testing every object, preferring front faces and then lower indices.
+    ///
+    /// # Arguments
+    ///
+    /// * `objects` - the objects the hierarchy was built for
+    /// * `ray` - the ray to trace along
+    /// * `filter` - a distance range in which to intersect
+    pub fn intersects<'a>(
+        &self,
+        objects: &'a [Object],
+        ray: &Ray,
+        filter: Range<f32>,
+    ) -> Option<(Intersection, &'a dyn Material)> {
+        let (closest, _) = self.traverse(objects, ray, filter);
+        closest.map(|(hit, index)| (hit, &*objects[index].material))
+    }
+
+    /// Find the closest intersection (and the index of its object), along
+    /// with the number of nodes visited to find it.
+    fn traverse(
+        &self,
+        objects: &[Object],
+        ray: &Ray,
+        filter: Range<f32>,
+    ) -> (Option<(Intersection, usize)>, usize) {
+        let mut closest = None;
+        let mut visits = 0;
+        if let Some(root) = &self.root {
+            root.intersects(objects, ray, &filter, &mut closest, &mut visits);
+        }
+        (closest, visits)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::materials::{Color, Lambertian};
+    use crate::surfaces::Sphere;
+    use crate::types::Vect3;
+    use pretty_assertions::assert_eq;
+
+    fn sphere(center: Point3, radius: f32) -> Object {
+        Object {
+            surface: Box::new(Sphere { center, radius }),
+            material: Box::new(Lambertian::new(Color(1.0, 1.0, 1.0))),
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let bvh = Bvh::new(&[]);
+        let ray = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
+
+        assert!(bvh.root.is_none());
+        assert!(bvh.intersects(&[], &ray, 0.0..f32::INFINITY).is_none());
+    }
+
+    #[test]
+    fn test_closest_intersection() {
+        let objects: Vec<Object> = (0..10)
+            .map(|i| sphere(Point3(0.0, 0.0, 10.0 - (i as f32)), 0.25))
+            .collect();
+        let bvh = Bvh::new(&objects);
+        let ray = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
+
+        let (hit, _) = bvh.intersects(&objects, &ray, 0.0..f32::INFINITY).unwrap();
+        assert_eq!(hit.point(), Point3(0.0, 0.0, 0.75));
+        let (hit, _) = bvh.intersects(&objects, &ray, 2.0..f32::INFINITY).unwrap();
+        assert_eq!(hit.point(), Point3(0.0, 0.0, 2.25));
+    }
+
+    #[test]
+    fn test_sah_matches_median() {
+        let (camera, scene) = crate::get_scene(2.0, "large").unwrap();
+        let median = Bvh::with_strategy(&scene.objects, BvhBuildStrategy::Median);
+        let sah = Bvh::with_strategy(&scene.objects, BvhBuildStrategy::Sah);
+        for (u, v) in (0..32).flat_map(|u| (0..16).map(move |v| (u, v))) {
+            let ray = camera.ray((u as f32) / 31.0, (v as f32) / 15.0);
+            let (expected, _) = median.traverse(&scene.objects, &ray, 0.001..f32::INFINITY);
+            let (actual, _) = sah.traverse(&scene.objects, &ray, 0.001..f32::INFINITY);
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_sah_skewed_scene() {
+        // A tight cluster of small spheres, and a single sphere far away.
+        let mut objects: Vec<Object> = (0..63)
+            .map(|i| {
+                sphere(
+                    Point3(0.1 * ((i % 8) as f32), 0.1 * ((i / 8) as f32), 0.0),
+                    0.05,
+                )
+            })
+            .collect();
+        objects.push(sphere(Point3(100.0, 0.0, 0.0), 1.0));
+        let median = Bvh::with_strategy(&objects, BvhBuildStrategy::Median);
+        let sah = Bvh::with_strategy(&objects, BvhBuildStrategy::Sah);
+        let visits = |bvh: &Bvh| {
+            (0..=100)
+                .map(|x| Ray::new(Point3(x as f32, 0.3, -5.0), Vect3(0.0, 0.0, 1.0)))
+                .map(|ray| bvh.traverse(&objects, &ray, 0.0..f32::INFINITY).1)
+                .sum::<usize>()
+        };
+
+        assert!(visits(&sah) < visits(&median));
+    }
+}
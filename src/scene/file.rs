@@ -0,0 +1,232 @@
+use super::{Object, Scene};
+use crate::camera::Camera;
+use crate::materials::*;
+use crate::surfaces::*;
+use crate::types::{Point3, Vect3};
+use serde::{Deserialize, Serialize};
+
+/// The camera of a scene file.
+///
+/// The viewport is derived from the aspect ratio of the rendered image, so
+/// the field of view is given as a (diagonal) angle of view, in degrees.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraDescription {
+    pub origin: Point3,
+    pub target: Point3,
+    pub vertical: Vect3,
+    pub angle_of_view: f32,
+    pub aperture: f32,
+}
+
+impl CameraDescription {
+    /// Construct the described camera for a given aspect ratio.
+    pub fn camera(&self, aspect_ratio: f32) -> Camera {
+        Camera::with_fov(
+            self.origin,
+            self.target,
+            self.vertical,
+            self.angle_of_view,
+            self.aperture,
+            aspect_ratio,
+        )
+    }
+}
+
+/// A surface in a scene file, tagged by its `type`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SurfaceDescription {
+    Sphere(Sphere),
+}
+
+impl SurfaceDescription {
+    /// Construct the described surface.
+    pub fn surface(&self) -> Box<dyn Surface> {
+        match *self {
+            SurfaceDescription::Sphere(sphere) => Box::new(sphere),
+        }
+    }
+}
+
+/// A material in a scene file, tagged by its `type`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MaterialDescription {
+    Lambertian { color: Color },
+    Hemispherical { color: Color },
+    Metal { color: Color, fuzziness: f32 },
+    Dielectric { color: Color, refraction: f32 },
+    DiffuseLight { color: Color, intensity: f32 },
+}
+
+impl MaterialDescription {
+    /// Construct the described material.
+    pub fn material(&self) -> Box<dyn Material> {
+        match *self {
+            MaterialDescription::Lambertian { color } => Box::new(Lambertian::new(color)),
+            MaterialDescription::Hemispherical { color } => Box::new(Hemispherical::new(color)),
+            MaterialDescription::Metal { color, fuzziness } => {
+                Box::new(Metal::new(color, fuzziness))
+            }
+            MaterialDescription::Dielectric { color, refraction } => {
+                Box::new(Dielectric::new(color, refraction))
+            }
+            MaterialDescription::DiffuseLight { color, intensity } => {
+                Box::new(DiffuseLight::new(color, intensity))
+            }
+        }
+    }
+}
+
+/// An object in a scene file.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ObjectDescription {
+    pub surface: SurfaceDescription,
+    pub material: MaterialDescription,
+}
+
+/// A serializable description of a scene (and its camera).
+///
+/// # Example
+///
+/// ```json
+/// {
+///   "camera": {
+///     "origin": [0.0, 0.0, 0.0],
+///     "target": [0.0, 0.0, -1.0],
+///     "vertical": [0.0, 1.0, 0.0],
+///     "angle_of_view": 40.0,
+///     "aperture": 16.0
+///   },
+///   "objects": [
+///     {
+///       "surface": { "type": "sphere", "center": [0.0, 0.0, -1.0], "radius": 0.5 },
+///       "material": { "type": "lambertian", "color": [0.1, 0.2, 0.5] }
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneDescription {
+    pub camera: CameraDescription,
+    pub objects: Vec<ObjectDescription>,
+}
+
+impl SceneDescription {
+    /// Parse a scene description from JSON.
+    pub fn from_json(json: &str) -> Result<SceneDescription, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Construct the described camera and scene for a given aspect ratio.
+    pub fn build(&self, aspect_ratio: f32) -> (Camera, Scene) {
+        let objects = self
+            .objects
+            .iter()
+            .map(|object| Object {
+                surface: object.surface.surface(),
+                material: object.material.material(),
+            })
+            .collect();
+        (
+            self.camera.camera(aspect_ratio),
+            Scene::from_objects(objects),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_two_spheres() {
+        let json = indoc::indoc! {r#"
+            {
+              "camera": {
+                "origin": [0.0, 1.0, 2.0],
+                "target": [0.0, 0.0, -1.0],
+                "vertical": [0.0, 1.0, 0.0],
+                "angle_of_view": 40.0,
+                "aperture": 16.0
+              },
+              "objects": [
+                {
+                  "surface": { "type": "sphere", "center": [0.0, 0.0, -1.0], "radius": 0.5 },
+                  "material": { "type": "lambertian", "color": [0.1, 0.2, 0.5] }
+                },
+                {
+                  "surface": { "type": "sphere", "center": [1.0, 0.0, -1.0], "radius": -0.4 },
+                  "material": { "type": "metal", "color": [0.8, 0.6, 0.2], "fuzziness": 0.1 }
+                }
+              ]
+            }
+        "#};
+        let description = SceneDescription::from_json(json).unwrap();
+
+        assert_eq!(
+            description.objects,
+            vec![
+                ObjectDescription {
+                    surface: SurfaceDescription::Sphere(Sphere {
+                        center: Point3(0.0, 0.0, -1.0),
+                        radius: 0.5,
+                    }),
+                    material: MaterialDescription::Lambertian {
+                        color: Color(0.1, 0.2, 0.5),
+                    },
+                },
+                ObjectDescription {
+                    surface: SurfaceDescription::Sphere(Sphere {
+                        center: Point3(1.0, 0.0, -1.0),
+                        radius: -0.4,
+                    }),
+                    material: MaterialDescription::Metal {
+                        color: Color(0.8, 0.6, 0.2),
+                        fuzziness: 0.1,
+                    },
+                },
+            ]
+        );
+        let (camera, scene) = description.build(2.0);
+        let viewport = (4.0_f32, 2.0_f32);
+        let diagonal = (viewport.0.powi(2) + viewport.1.powi(2)).sqrt();
+        let focal_length = (diagonal / 2.0) / (40.0_f32.to_radians() / 2.0).tan();
+        let expected = Camera::new(
+            Point3(0.0, 1.0, 2.0),
+            Point3(0.0, 0.0, -1.0),
+            Vect3(0.0, 1.0, 0.0),
+            focal_length,
+            16.0,
+            viewport,
+        );
+        assert_eq!(camera, expected);
+        assert_eq!(scene.objects.len(), 2);
+        let metal = scene.objects[1].material.downcast_ref::<Metal>().unwrap();
+        assert_eq!(metal.fuzziness(), 0.1);
+    }
+
+    #[test]
+    fn test_invalid_material() {
+        let json = indoc::indoc! {r#"
+            {
+              "camera": {
+                "origin": [0.0, 0.0, 0.0],
+                "target": [0.0, 0.0, -1.0],
+                "vertical": [0.0, 1.0, 0.0],
+                "angle_of_view": 40.0,
+                "aperture": 16.0
+              },
+              "objects": [
+                {
+                  "surface": { "type": "sphere", "center": [0.0, 0.0, -1.0], "radius": 0.5 },
+                  "material": { "type": "plastic", "color": [0.1, 0.2, 0.5] }
+                }
+              ]
+            }
+        "#};
+
+        assert!(SceneDescription::from_json(json).is_err());
+    }
+}
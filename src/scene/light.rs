@@ -0,0 +1,12 @@
+use crate::materials::Color;
+use crate::types::Point3;
+
+/// An infinitely small light, emitting light uniformly in all directions.
+///
+/// Point lights are invisible, and can never be hit by a scattered ray.
+/// Instead, they directly illuminate every diffuse surface they can see.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointLight {
+    pub position: Point3,
+    pub intensity: Color,
+}
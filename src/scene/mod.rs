@@ -0,0 +1,973 @@
+/// Scenes, composed of objects, and the structures used to render them.
+mod background;
+mod builder;
+mod bvh;
+mod file;
+mod light;
+
+// Exports.
+pub use background::Background;
+pub use builder::SceneBuilder;
+pub use bvh::BvhBuildStrategy;
+pub use file::SceneDescription;
+pub use light::PointLight;
+
+// Imports.
+use crate::image;
+use crate::materials::*;
+use crate::sampler;
+use crate::surfaces::*;
+use crate::types::Ray;
+use bvh::Bvh;
+use rand::Rng;
+use std::cmp::Ordering;
+use std::f32::consts::PI;
+use std::ops::Range;
+
+/// An object, defined as a surface with a material.
+pub struct Object {
+    pub surface: Box<dyn Surface>,
+    pub material: Box<dyn Material>,
+}
+
+impl Object {
+    /// Construct an object from a surface and a material.
+    pub fn new(surface: impl Surface + 'static, material: impl Material + 'static) -> Object {
+        Object {
+            surface: Box::new(surface),
+            material: Box::new(material),
+        }
+    }
+}
+
+/// A component of the light reaching the camera, separated by the lobe
+/// (diffuse or specular) which first scattered it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Component {
+    /// All light (i.e. the full "beauty" render).
+    #[default]
+    Beauty,
+    /// Only light scattered by diffuse materials.
+    DiffuseOnly,
+    /// Only light reflected or refracted by specular materials.
+    SpecularOnly,
+}
+
+/// A full, renderable "scene".
+///
+/// Intersections are accelerated using a bounding volume hierarchy, which
+/// is dropped whenever the objects of the scene may change, and must then be
+/// rebuilt (using `build_bvh`). Until then, the scene falls back to testing
+/// every object.
+pub struct Scene {
+    objects: Vec<Object>,
+    pub background: Background,
+    pub lights: Vec<PointLight>,
+    roulette: Option<usize>,
+    bvh: Option<Bvh>,
+}
+
+/// Order intersections (tagged with the index of their object) by distance.
+///
+/// Intersections at equal distance are ordered deterministically: hits on
+/// the front of a surface come first, followed by the lowest object index.
+/// Intersections at a NaN distance come last.
+fn hit_order(a: &(Intersection, usize), b: &(Intersection, usize)) -> Ordering {
+    let (distance_a, distance_b) = (a.0.distance_squared(), b.0.distance_squared());
+    let distance = match (distance_a.is_nan(), distance_b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        _ => distance_a.partial_cmp(&distance_b).unwrap(),
+    };
+    distance
+        .then(b.0.front_face().cmp(&a.0.front_face()))
+        .then(a.1.cmp(&b.1))
+}
+
+impl Ray {
+    /// Check whether a ray intersects any surface in a scene.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - the ray to trace along
+    /// * `scene` - the scene to intersect in
+    /// * `filter` - a distance range in which to intersect
+    fn intersects<'a>(
+        &self,
+        scene: &'a Scene,
+        filter: Range<f32>,
+    ) -> Option<(Intersection, &'a dyn Material)> {
+        match &scene.bvh {
+            Some(bvh) => bvh.intersects(&scene.objects, self, filter),
+            None => self.intersects_each(scene, filter),
+        }
+    }
+
+    /// Check whether a ray intersects any surface in a scene, by testing
+    /// every object of the scene in turn.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - the ray to trace along
+    /// * `scene` - the scene to intersect in
+    /// * `filter` - a distance range in which to intersect
+    fn intersects_each<'a>(
+        &self,
+        scene: &'a Scene,
+        filter: Range<f32>,
+    ) -> Option<(Intersection, &'a dyn Material)> {
+        scene
+            .objects
+            .iter()
+            .enumerate()
+            .flat_map(|(index, object)| {
+                object
+                    .surface
+                    .intersected_by(self, filter.clone())
+                    .into_iter()
+                    .map(move |intersection| (intersection, index))
+            })
+            .min_by(hit_order)
+            .map(|(hit, index)| (hit, &*scene.objects[index].material))
+    }
+}
+
+impl Default for Scene {
+    fn default() -> Scene {
+        Scene::new()
+    }
+}
+
+impl Scene {
+    /// Construct an empty scene.
+    pub fn new() -> Scene {
+        Scene::from_objects(vec![])
+    }
+
+    /// Construct a scene from a list of objects.
+    pub fn from_objects(objects: Vec<Object>) -> Scene {
+        let mut scene = Scene {
+            objects,
+            background: Background::default(),
+            lights: vec![],
+            roulette: None,
+            bvh: None,
+        };
+        scene.build_bvh();
+        scene
+    }
+
+    /// Add an object to the scene.
+    ///
+    /// This drops the bounding volume hierarchy (rather than rebuilding it
+    /// after every object), so `build_bvh` should be called once all objects
+    /// have been added.
+    ///
+    /// # Arguments
+    ///
+    /// * `surface` - the surface of the object
+    /// * `material` - the material of the object
+    pub fn add(&mut self, surface: impl Surface + 'static, material: impl Material + 'static) {
+        self.objects.push(Object::new(surface, material));
+        self.bvh = None;
+    }
+
+    /// Change the background of the scene.
+    pub fn with_background(self, background: Background) -> Scene {
+        Scene { background, ..self }
+    }
+
+    /// Change the point lights illuminating the scene.
+    pub fn with_lights(self, lights: Vec<PointLight>) -> Scene {
+        Scene { lights, ..self }
+    }
+
+    /// Terminate paths randomly using Russian roulette.
+    ///
+    /// After the first `min_bounces` bounces, a scattered ray is traced
+    /// with a probability equal to the largest channel of its attenuation
+    /// (and its contribution scaled up accordingly), so dark paths are cut
+    /// short without biasing the rendered color. The path is still limited
+    /// by the recursion depth.
+    pub fn with_russian_roulette(self, min_bounces: usize) -> Scene {
+        Scene {
+            roulette: Some(min_bounces),
+            ..self
+        }
+    }
+
+    /// Get the probability of tracing a scattered ray after a number of bounces.
+    fn survival_probability(&self, attenuation: &Color, bounces: usize) -> f32 {
+        match self.roulette {
+            Some(min_bounces) if bounces >= min_bounces => attenuation
+                .red()
+                .max(attenuation.green())
+                .max(attenuation.blue())
+                .min(1.0),
+            _ => 1.0,
+        }
+    }
+
+    /// Rebuild the bounding volume hierarchy of the scene.
+    ///
+    /// This should be done after adding, removing or modifying the surfaces
+    /// of the scene objects.
+    pub fn build_bvh(&mut self) {
+        self.bvh = Some(Bvh::new(&self.objects));
+    }
+
+    /// Rebuild the bounding volume hierarchy using a specific split strategy.
+    pub fn build_bvh_with(&mut self, strategy: BvhBuildStrategy) {
+        self.bvh = Some(Bvh::with_strategy(&self.objects, strategy));
+    }
+
+    /// Get the objects of the scene.
+    pub fn objects(&self) -> &[Object] {
+        &self.objects
+    }
+
+    /// Get the objects of the scene, allowing modification.
+    ///
+    /// This drops the bounding volume hierarchy, so `build_bvh` should be
+    /// called once the objects have been modified.
+    pub fn objects_mut(&mut self) -> &mut Vec<Object> {
+        self.bvh = None;
+        &mut self.objects
+    }
+
+    /// Iterate over all objects matching a predicate, allowing modification.
+    ///
+    /// This drops the bounding volume hierarchy, so `build_bvh` should be
+    /// called once the objects have been modified.
+    ///
+    /// # Arguments
+    ///
+    /// * `pred` - predicate that selects the objects to return
+    pub fn objects_matching_mut<P>(&mut self, pred: P) -> impl Iterator<Item = &mut Object>
+    where
+        P: Fn(&Object) -> bool,
+    {
+        self.objects_mut()
+            .iter_mut()
+            .filter(move |object| pred(object))
+    }
+
+    /// Compute the total power emitted by all objects in the scene.
+    ///
+    /// The power of each object is its emitted radiance scaled by its area.
+    pub fn total_emitted_power(&self) -> Color {
+        self.objects
+            .iter()
+            .map(|object| object.material.emitted() * object.surface.area())
+            .fold(Color::default(), |acc, power| acc + power)
+    }
+
+    /// Find the closest intersection between a ray and the scene, if any.
+    pub fn first_hit(&self, ray: &Ray) -> Option<Intersection> {
+        ray.intersects(self, 0.001..f32::INFINITY)
+            .map(|(intersection, _)| intersection)
+    }
+
+    /// Render the color for a specific ray.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - the ray to trace along
+    /// * `depth` - max number of reflections
+    pub fn render_ray(&self, ray: &Ray, depth: usize) -> image::Pixel {
+        self.render_ray_component(ray, depth, Component::Beauty, 0.0)
+    }
+
+    /// Render a single component of the color for a specific ray.
+    ///
+    /// The component is selected by the lobe (diffuse or specular) of the
+    /// first scattering along each path, so e.g. `SpecularOnly` renders the
+    /// reflections in a mirror (or the glossy highlights of a Phong material)
+    /// but leaves purely diffuse surfaces black. Rays that miss the scene
+    /// entirely are only included in the `Beauty` component.
+    ///
+    /// Paths are cut short once their throughput (i.e. the luminance of the
+    /// product of all attenuations along the path) drops below `cutoff`,
+    /// since any further bounces would contribute very little light.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - the ray to trace along
+    /// * `depth` - max number of reflections
+    /// * `component` - the component of the light to render
+    /// * `cutoff` - smallest path throughput to keep tracing
+    pub fn render_ray_component(
+        &self,
+        ray: &Ray,
+        depth: usize,
+        component: Component,
+        cutoff: f32,
+    ) -> image::Pixel {
+        let miss = |ray: &Ray| self.background.color(ray);
+        let white = Color(1.0, 1.0, 1.0);
+        self.trace(ray, depth, 0, white, None, component, cutoff, &miss)
+    }
+
+    /// Render the color for a specific ray, using a custom background.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - the ray to trace along
+    /// * `depth` - max number of reflections
+    /// * `miss` - the color of rays that do not intersect any object
+    pub fn render_ray_with(
+        &self,
+        ray: &Ray,
+        depth: usize,
+        miss: &dyn Fn(&Ray) -> image::Pixel,
+    ) -> image::Pixel {
+        let white = Color(1.0, 1.0, 1.0);
+        self.trace(ray, depth, 0, white, None, Component::Beauty, 0.0, miss)
+    }
+
+    /// Compute the light reflected by a diffuse surface directly from the
+    /// point lights of the scene.
+    ///
+    /// A shadow ray is cast towards each light, and the light only
+    /// contributes if no surface is in the way.
+    ///
+    /// # Arguments
+    ///
+    /// * `intersection` - the intersection to illuminate
+    /// * `albedo` - the albedo of the surface at the intersection
+    /// * `time` - the time at which to cast the shadow rays
+    fn direct_light(&self, intersection: &Intersection, albedo: &Color, time: f32) -> image::Pixel {
+        self.lights
+            .iter()
+            .filter_map(|light| {
+                let offset = light.position - intersection.point();
+                let distance = offset.norm();
+                let cosine = intersection.normal().dot(offset) / distance;
+                if cosine <= 0.0 {
+                    return None;
+                }
+                let shadow = Ray::new(intersection.point(), offset).at_time(time);
+                if shadow.intersects(self, 0.001..distance).is_some() {
+                    return None;
+                }
+                // Lambertian reflection, with the inverse-square falloff of the light.
+                let scale = cosine / (PI * distance * distance);
+                Some(image::Pixel(
+                    albedo.red() * light.intensity.red() * scale,
+                    albedo.green() * light.intensity.green() * scale,
+                    albedo.blue() * light.intensity.blue() * scale,
+                ))
+            })
+            .fold(image::Pixel::default(), |acc, pixel| acc + pixel)
+    }
+
+    /// Render the color for a specific ray, after a number of bounces.
+    ///
+    /// The `throughput` is the product of the attenuations along the path so
+    /// far, and the path is terminated once its luminance is below `cutoff`.
+    ///
+    /// The light added at each bounce is attributed to a component by the
+    /// lobe which first scattered the path (`lobe`, which is `None` before
+    /// the first scattering). Light emitted (or reflected directly from point
+    /// lights) at the first hit is diffuse, while rays missing the scene
+    /// before scattering only contribute to the `Beauty` component.
+    ///
+    /// Paths are followed iteratively for as long as each material scatters
+    /// a single ray, carrying the attenuation accumulated along the way. Only
+    /// materials scattering several rays are traced recursively (averaging
+    /// the color of each scattered ray).
+    #[allow(clippy::too_many_arguments)]
+    fn trace(
+        &self,
+        ray: &Ray,
+        depth: usize,
+        bounces: usize,
+        throughput: Color,
+        lobe: Option<Lobe>,
+        component: Component,
+        cutoff: f32,
+        miss: &dyn Fn(&Ray) -> image::Pixel,
+    ) -> image::Pixel {
+        let keep = |lobe: Option<Lobe>| {
+            matches!(
+                (component, lobe),
+                (Component::Beauty, _)
+                    | (Component::DiffuseOnly, Some(Lobe::Diffuse))
+                    | (Component::SpecularOnly, Some(Lobe::Specular))
+            )
+        };
+        let mut ray = *ray;
+        let mut throughput = throughput;
+        let mut lobe = lobe;
+        let mut color = image::Pixel::default();
+        let mut weight = image::Pixel(1.0, 1.0, 1.0);
+        for (bounces, depth) in (bounces..).zip((1..=depth).rev()) {
+            let Some((intersection, material)) = ray.intersects(self, 0.001..f32::INFINITY) else {
+                let background = if keep(lobe) {
+                    miss(&ray)
+                } else {
+                    image::Pixel::default()
+                };
+                return color + weight * background;
+            };
+            // We have an intersection! Add any emitted light, then scatter the ray.
+            if keep(lobe.or(Some(Lobe::Diffuse))) {
+                let emitted = material.emitted();
+                let emitted = image::Pixel(emitted.red(), emitted.green(), emitted.blue());
+                let emitted = match material.diffuse_albedo(&intersection) {
+                    Some(albedo) => emitted + self.direct_light(&intersection, &albedo, ray.time()),
+                    None => emitted,
+                };
+                color += weight * emitted;
+            }
+            let scatters = material.scatter_lobes(&ray, &intersection);
+            // Follow a scattered ray, returning its relative weight (if it survives).
+            let follow = |attenuation: &Color| {
+                let throughput = throughput * attenuation;
+                if throughput.luminance() < cutoff {
+                    return None;
+                }
+                let survival = self.survival_probability(attenuation, bounces);
+                if survival < 1.0 && sampler::rng().gen::<f32>() >= survival {
+                    return None;
+                }
+                let attenuation =
+                    image::Pixel(attenuation.red(), attenuation.green(), attenuation.blue());
+                Some((throughput, attenuation / survival))
+            };
+            match scatters.as_slice() {
+                [] => return color,
+                [(reflected, attenuation, scattered)] => {
+                    lobe = lobe.or(Some(*scattered));
+                    match follow(attenuation) {
+                        Some((next, scale)) if keep(lobe) => {
+                            // Scattered rays are cast at the same time as the incoming ray.
+                            ray = reflected.at_time(ray.time());
+                            throughput = next;
+                            weight *= scale;
+                        }
+                        _ => return color,
+                    }
+                }
+                scatters => {
+                    // Average the attenuated color of each scattered ray, skipping
+                    // (but still counting) the rays of other components.
+                    let acc = scatters
+                        .iter()
+                        .filter(|(_, _, scattered)| keep(lobe.or(Some(*scattered))))
+                        .filter_map(|(reflected, attenuation, scattered)| {
+                            let (throughput, scale) = follow(attenuation)?;
+                            let reflected = reflected.at_time(ray.time());
+                            let traced = self.trace(
+                                &reflected,
+                                depth - 1,
+                                bounces + 1,
+                                throughput,
+                                lobe.or(Some(*scattered)),
+                                component,
+                                cutoff,
+                                miss,
+                            );
+                            Some(traced * scale)
+                        })
+                        .fold(image::Pixel::default(), |acc, pixel| acc + pixel);
+                    return color + weight * acc / (scatters.len() as f32);
+                }
+            }
+        }
+        // We reached the recursion depth, so no more light is added.
+        color
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::{Point3, Vect3};
+    use approx::assert_abs_diff_eq;
+    use pretty_assertions::assert_eq;
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    #[test]
+    fn test_intersection_filter() {
+        let material = || Lambertian::new(Color(1.0, 1.0, 1.0));
+        let sphere = Sphere {
+            center: Point3(0.0, 0.0, 2.0),
+            radius: 1.0,
+        };
+        let ray = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
+
+        let scene = Scene::from_objects(vec![Object {
+            surface: Box::new(sphere),
+            material: Box::new(material()),
+        }]);
+        assert!(ray.intersects(&scene, 0.0..f32::INFINITY).is_some());
+        assert!(ray.intersects(&scene, 0.0..0.5).is_none());
+        assert!(ray.intersects(&scene, 1.5..2.0).is_none());
+    }
+
+    #[test]
+    fn test_multiple_objects() {
+        let material = || Lambertian::new(Color(1.0, 1.0, 1.0));
+        let sphere_a = Sphere {
+            center: Point3(0.0, 0.0, 2.0),
+            radius: 1.0,
+        };
+        let sphere_b = Sphere {
+            center: Point3(0.0, 0.0, 4.0),
+            radius: 1.0,
+        };
+        let ray = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
+
+        let scene = Scene::from_objects(vec![
+            Object {
+                surface: Box::new(sphere_a),
+                material: Box::new(material()),
+            },
+            Object {
+                surface: Box::new(sphere_b),
+                material: Box::new(material()),
+            },
+        ]);
+        assert_eq!(
+            ray.intersects(&scene, 0.0..f32::INFINITY)
+                .map(|(intersection, _)| intersection.point()),
+            Some(Point3(0.0, 0.0, 1.0))
+        );
+        assert_eq!(
+            ray.intersects(&scene, 2.0..f32::INFINITY)
+                .map(|(intersection, _)| intersection.point()),
+            Some(Point3(0.0, 0.0, 3.0))
+        );
+    }
+
+    #[test]
+    fn test_add() {
+        let mut scene = Scene::new();
+        let ray = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
+        assert_eq!(scene.objects.len(), 0);
+        assert!(ray.intersects(&scene, 0.0..f32::INFINITY).is_none());
+
+        scene.add(
+            Sphere {
+                center: Point3(0.0, 0.0, 2.0),
+                radius: 1.0,
+            },
+            Lambertian::new(Color(1.0, 1.0, 1.0)),
+        );
+        assert_eq!(scene.objects.len(), 1);
+        assert!(scene.bvh.is_none());
+        assert_eq!(
+            ray.intersects(&scene, 0.0..f32::INFINITY)
+                .map(|(intersection, _)| intersection.point()),
+            Some(Point3(0.0, 0.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn test_render_ray_with_miss_closure() {
+        let scene = Scene::from_objects(vec![]);
+        let color = image::Pixel(0.25, 0.5, 0.75);
+        let miss = |_: &Ray| color;
+
+        for direction in [
+            Vect3(0.0, 0.0, 1.0),
+            Vect3(1.0, -1.0, 0.0),
+            Vect3(0.0, 1.0, 0.0),
+        ] {
+            let ray = Ray::new(Point3::zero(), direction);
+            assert_eq!(scene.render_ray_with(&ray, 10, &miss), color);
+        }
+    }
+
+    #[test]
+    fn test_components_per_lobe() {
+        let glossy = Phong::new(Color(0.5, 0.5, 0.5), Color(0.5, 0.5, 0.5), 10.0);
+        let mixed = Mix::new(
+            Box::new(Metal::new(Color(0.8, 0.8, 0.8), 0.0)),
+            Box::new(Lambertian::new(Color(0.8, 0.8, 0.8))),
+            0.5,
+        );
+        let materials: [Box<dyn Material>; 2] = [Box::new(glossy), Box::new(mixed)];
+        for material in materials {
+            let scene = Scene::from_objects(vec![Object {
+                surface: Box::new(Sphere {
+                    center: Point3(0.0, 0.0, 2.0),
+                    radius: 1.0,
+                }),
+                material,
+            }])
+            .with_background(Background::Solid(Color(1.0, 1.0, 1.0)));
+            let ray = Ray::new(Point3::zero(), Vect3(0.1, 0.2, 1.0));
+            let average = |component: Component| {
+                let samples = 4000;
+                (0..samples)
+                    .map(|_| {
+                        scene
+                            .render_ray_component(&ray, 4, component, 0.0)
+                            .luminance()
+                    })
+                    .sum::<f32>()
+                    / (samples as f32)
+            };
+
+            // Both lobes contribute, and add up to the full render.
+            let diffuse = average(Component::DiffuseOnly);
+            let specular = average(Component::SpecularOnly);
+            assert!(diffuse > 0.1);
+            assert!(specular > 0.1);
+            assert!((diffuse + specular - average(Component::Beauty)).abs() < 0.03);
+        }
+    }
+
+    #[test]
+    fn test_solid_background() {
+        let sphere = Object {
+            surface: Box::new(Sphere {
+                center: Point3(0.0, 0.0, 2.0),
+                radius: 1.0,
+            }),
+            material: Box::new(Lambertian::new(Color(1.0, 1.0, 1.0))),
+        };
+        let scene = Scene::from_objects(vec![sphere])
+            .with_background(Background::Solid(Color(0.0, 0.0, 0.0)));
+
+        for direction in [
+            Vect3(0.0, 0.0, -1.0),
+            Vect3(1.0, -1.0, 0.0),
+            Vect3(0.0, 1.0, 0.0),
+        ] {
+            let ray = Ray::new(Point3::zero(), direction);
+            assert_eq!(scene.render_ray(&ray, 10), image::Pixel::default());
+        }
+        // Light bounced off the sphere can only come from the background.
+        let ray = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
+        assert_eq!(scene.render_ray(&ray, 10), image::Pixel::default());
+    }
+
+    #[test]
+    fn test_russian_roulette_is_unbiased() {
+        let sphere = |center: Point3, radius: f32| Object {
+            surface: Box::new(Sphere { center, radius }),
+            material: Box::new(Lambertian::new(Color(0.5, 0.5, 0.5))),
+        };
+        let objects = || {
+            vec![
+                sphere(Point3(0.0, 0.0, -1.0), 0.5),
+                sphere(Point3(0.0, -100.5, -1.0), 100.0),
+            ]
+        };
+        let fixed = Scene::from_objects(objects());
+        let roulette = Scene::from_objects(objects()).with_russian_roulette(1);
+        let ray = Ray::new(Point3::zero(), Vect3(0.0, -0.2, -1.0));
+        let estimate = |scene: &Scene| {
+            (0..20000)
+                .map(|_| scene.render_ray(&ray, 10).luminance())
+                .sum::<f32>()
+                / 20000.0
+        };
+
+        let expected = estimate(&fixed);
+        assert!(expected > 0.0);
+        assert!((estimate(&roulette) - expected).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_contribution_cutoff() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        // A dim mirror counting the number of times it scatters a ray.
+        struct Counting(AtomicUsize);
+        impl Material for Counting {
+            fn scatter_at(&self, ray: &Ray, intersection: &Intersection) -> Vec<(Ray, Color)> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                let reflected = ray.direction().reflect(intersection.normal());
+                vec![(
+                    Ray::new(intersection.point(), reflected),
+                    Color(0.5, 0.5, 0.5),
+                )]
+            }
+        }
+        // A ray bouncing back and forth inside a mirrored sphere.
+        let scene = Scene::from_objects(vec![Object {
+            surface: Box::new(Sphere {
+                center: Point3::zero(),
+                radius: -1.0,
+            }),
+            material: Box::new(Counting(AtomicUsize::new(0))),
+        }]);
+        let ray = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
+        let bounces = |cutoff: f32| {
+            scene.render_ray_component(&ray, 50, Component::Beauty, cutoff);
+            let counting = scene.objects[0]
+                .material
+                .downcast_ref::<Counting>()
+                .unwrap();
+            counting.0.swap(0, Ordering::SeqCst)
+        };
+
+        assert_eq!(bounces(0.0), 50);
+        // The throughput after `n` bounces is `0.5^n`, which is below 0.01 at 7.
+        assert_eq!(bounces(0.01), 7);
+    }
+
+    #[test]
+    fn test_objects_matching_mut() {
+        let sphere = Sphere {
+            center: Point3::zero(),
+            radius: 1.0,
+        };
+        let mut scene = Scene::from_objects(vec![
+            Object {
+                surface: Box::new(sphere),
+                material: Box::new(Metal::new(Color(1.0, 1.0, 1.0), 0.0)),
+            },
+            Object {
+                surface: Box::new(sphere),
+                material: Box::new(Lambertian::new(Color(1.0, 1.0, 1.0))),
+            },
+            Object {
+                surface: Box::new(sphere),
+                material: Box::new(Metal::new(Color(1.0, 1.0, 1.0), 0.1)),
+            },
+        ]);
+
+        let is_metal = |object: &Object| object.material.downcast_ref::<Metal>().is_some();
+        for object in scene.objects_matching_mut(is_metal) {
+            let metal = object.material.downcast_mut::<Metal>().unwrap();
+            metal.set_fuzziness(metal.fuzziness() + 0.5);
+        }
+        let fuzziness: Vec<Option<f32>> = scene
+            .objects
+            .iter()
+            .map(|object| {
+                object
+                    .material
+                    .downcast_ref::<Metal>()
+                    .map(Metal::fuzziness)
+            })
+            .collect();
+        assert_eq!(fuzziness, vec![Some(0.5), None, Some(0.6)]);
+    }
+
+    #[test]
+    fn test_objects_mut_drops_bvh() {
+        let sphere = |center: Point3| Object {
+            surface: Box::new(Sphere {
+                center,
+                radius: 1.0,
+            }),
+            material: Box::new(Lambertian::new(Color(1.0, 1.0, 1.0))),
+        };
+        let mut scene = Scene::from_objects(vec![sphere(Point3(0.0, 0.0, 5.0))]);
+        let ray = Ray::new(Point3::zero(), Vect3(1.0, 0.0, 0.0));
+        assert!(ray.intersects(&scene, 0.0..f32::INFINITY).is_none());
+
+        // Moving an object keeps the number of objects, but must still be seen.
+        scene.objects_mut()[0] = sphere(Point3(5.0, 0.0, 0.0));
+        assert!(scene.bvh.is_none());
+        assert!(ray.intersects(&scene, 0.0..f32::INFINITY).is_some());
+        scene.build_bvh();
+        scene.objects_matching_mut(|_| false).for_each(drop);
+        assert!(scene.bvh.is_none());
+    }
+
+    #[test]
+    fn test_trace_matches_recursive() {
+        // The recursive tracing of a ray, as a reference.
+        fn recursive(scene: &Scene, ray: &Ray, depth: usize) -> image::Pixel {
+            if depth == 0 {
+                return image::Pixel::default();
+            }
+            match ray.intersects(scene, 0.001..f32::INFINITY) {
+                Some((intersection, material)) => {
+                    let emitted = material.emitted();
+                    let scatters = material.scatter_at(ray, &intersection);
+                    let acc = scatters
+                        .iter()
+                        .map(|(reflected, attenuation)| {
+                            recursive(scene, reflected, depth - 1)
+                                * image::Pixel(
+                                    attenuation.red(),
+                                    attenuation.green(),
+                                    attenuation.blue(),
+                                )
+                        })
+                        .fold(image::Pixel::default(), |acc, pixel| acc + pixel);
+                    let scale = (scatters.len().max(1) as f32).recip();
+                    image::Pixel(emitted.red(), emitted.green(), emitted.blue()) + acc * scale
+                }
+                None => scene.background.color(ray),
+            }
+        }
+        let (camera, scene) = crate::get_scene(2.0, "small").unwrap();
+        let ray = camera.ray(0.5, 0.5);
+        let average = |render: &dyn Fn() -> image::Pixel| {
+            let samples = 2000;
+            (0..samples).map(|_| render().luminance()).sum::<f32>() / (samples as f32)
+        };
+
+        let iterative = average(&|| scene.render_ray(&ray, 50));
+        let expected = average(&|| recursive(&scene, &ray, 50));
+        assert!(expected > 0.0);
+        assert!((iterative - expected).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_diffuse_light_illuminates() {
+        let sphere = || Object {
+            surface: Box::new(Sphere {
+                center: Point3(0.0, 0.0, 0.0),
+                radius: 1.0,
+            }),
+            material: Box::new(Lambertian::new(Color(0.8, 0.8, 0.8))),
+        };
+        let light = Object {
+            surface: Box::new(Disk {
+                center: Point3(0.0, 2.0, 0.0),
+                normal: Vect3(0.0, -1.0, 0.0),
+                radius: 2.0,
+            }),
+            material: Box::new(DiffuseLight::new(Color(1.0, 1.0, 1.0), 4.0)),
+        };
+        let dark = Scene::from_objects(vec![sphere()]);
+        let lit = Scene::from_objects(vec![sphere(), light]);
+        let black = |_: &Ray| image::Pixel::default();
+        let ray = Ray::new(Point3(0.0, 1.5, -3.0), Vect3(0.0, -0.5, 3.0));
+        let brightness = |scene: &Scene| {
+            (0..100)
+                .map(|_| scene.render_ray_with(&ray, 4, &black).luminance())
+                .sum::<f32>()
+        };
+
+        assert_eq!(brightness(&dark), 0.0);
+        assert!(brightness(&lit) > 0.0);
+    }
+
+    #[test]
+    fn test_point_light_shadow() {
+        let sphere = |center: Point3, radius: f32| Object {
+            surface: Box::new(Sphere { center, radius }),
+            material: Box::new(Lambertian::new(Color(0.8, 0.8, 0.8))),
+        };
+        let light = PointLight {
+            position: Point3(3.0, 0.0, 4.0),
+            intensity: Color(10.0, 10.0, 10.0),
+        };
+        let lit = Scene::from_objects(vec![sphere(Point3::zero(), 1.0)]).with_lights(vec![light]);
+        let shadowed = Scene::from_objects(vec![
+            sphere(Point3::zero(), 1.0),
+            sphere(Point3(1.5, 0.0, 2.5), 0.5),
+        ])
+        .with_lights(vec![light]);
+        // Scattered rays are not traced at depth 1, so only direct light remains.
+        let black = |_: &Ray| image::Pixel::default();
+        let ray = Ray::new(Point3(0.0, 0.0, 5.0), Vect3(0.0, 0.0, -1.0));
+
+        let expected = 0.8 * 10.0 * FRAC_1_SQRT_2 / (PI * 18.0);
+        let pixel = lit.render_ray_with(&ray, 1, &black);
+        for channel in [pixel.red(), pixel.green(), pixel.blue()] {
+            assert_abs_diff_eq!(channel, expected, epsilon = 1e-6);
+        }
+        assert_eq!(
+            shadowed.render_ray_with(&ray, 1, &black),
+            image::Pixel::default()
+        );
+    }
+
+    #[test]
+    fn test_point_light_phong() {
+        let phong = Phong::new(Color(0.8, 0.8, 0.8), Color(0.2, 0.2, 0.2), 10.0);
+        let scene = Scene::from_objects(vec![Object {
+            surface: Box::new(Sphere {
+                center: Point3::zero(),
+                radius: 1.0,
+            }),
+            material: Box::new(phong),
+        }])
+        .with_lights(vec![PointLight {
+            position: Point3(3.0, 0.0, 4.0),
+            intensity: Color(10.0, 10.0, 10.0),
+        }]);
+        // Scattered rays are not traced at depth 1, so only direct light remains.
+        let black = |_: &Ray| image::Pixel::default();
+        let ray = Ray::new(Point3(0.0, 0.0, 5.0), Vect3(0.0, 0.0, -1.0));
+
+        // The diffuse lobe is lit just like a lambertian surface.
+        let expected = 0.8 * 10.0 * FRAC_1_SQRT_2 / (PI * 18.0);
+        let pixel = scene.render_ray_with(&ray, 1, &black);
+        for channel in [pixel.red(), pixel.green(), pixel.blue()] {
+            assert_abs_diff_eq!(channel, expected, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_total_emitted_power() {
+        let radius = 2.0;
+        let scene = Scene::from_objects(vec![
+            Object {
+                surface: Box::new(Sphere {
+                    center: Point3(0.0, 0.0, 0.0),
+                    radius,
+                }),
+                material: Box::new(DiffuseLight::new(Color(1.0, 0.5, 0.25), 2.0)),
+            },
+            Object {
+                surface: Box::new(Sphere {
+                    center: Point3(0.0, 5.0, 0.0),
+                    radius: 1.0,
+                }),
+                material: Box::new(Lambertian::new(Color(1.0, 1.0, 1.0))),
+            },
+        ]);
+
+        let area = 4.0 * std::f32::consts::PI * radius * radius;
+        let power = scene.total_emitted_power();
+        assert!((power.red() - 2.0 * area).abs() < 1e-4);
+        assert!((power.green() - 1.0 * area).abs() < 1e-4);
+        assert!((power.blue() - 0.5 * area).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_bvh_matches_brute_force() {
+        for name in crate::list_scenes() {
+            let (camera, scene) = crate::get_scene(2.0, name).unwrap();
+            for (u, v) in (0..32).flat_map(|u| (0..16).map(move |v| (u, v))) {
+                let ray = camera.ray((u as f32) / 31.0, (v as f32) / 15.0);
+                let accelerated = ray.intersects(&scene, 0.001..f32::INFINITY);
+                let brute_force = ray.intersects_each(&scene, 0.001..f32::INFINITY);
+                assert_eq!(
+                    accelerated.map(|(hit, _)| hit),
+                    brute_force.map(|(hit, _)| hit)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_coincident_tie_breaking() {
+        let sphere = |radius: f32, fuzziness: f32| Object {
+            surface: Box::new(Sphere {
+                center: Point3(0.0, 0.0, 0.0),
+                radius,
+            }),
+            material: Box::new(Metal::new(Color(1.0, 1.0, 1.0), fuzziness)),
+        };
+        // An inside-out sphere is hit on its back face, so loses to a regular
+        // sphere even though it comes first. Identical spheres are resolved
+        // by their order in the scene.
+        let mut scene =
+            Scene::from_objects(vec![sphere(-1.0, 0.1), sphere(1.0, 0.2), sphere(1.0, 0.3)]);
+        let ray = Ray::new(Point3(0.0, 0.0, -5.0), Vect3(0.0, 0.0, 1.0));
+        let winner = |scene: &Scene| {
+            let (_, material) = ray.intersects(scene, 0.001..f32::INFINITY).unwrap();
+            material.downcast_ref::<Metal>().unwrap().fuzziness()
+        };
+
+        for _ in 0..10 {
+            assert_eq!(winner(&scene), 0.2);
+        }
+        scene.bvh = None;
+        assert_eq!(winner(&scene), 0.2);
+    }
+}
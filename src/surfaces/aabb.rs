@@ -0,0 +1,148 @@
+use crate::types::{Point3, Ray, Vect3};
+use std::ops::Range;
+
+/// An axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Point3,
+    pub max: Point3,
+}
+
+impl Aabb {
+    /// Construct the smallest bounding box containing a set of points.
+    pub fn from_points(points: &[Point3]) -> Aabb {
+        let min = Point3(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+        let max = Point3(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+        points.iter().fold(Aabb { min, max }, |bounds, point| Aabb {
+            min: Point3(
+                bounds.min.x().min(point.x()),
+                bounds.min.y().min(point.y()),
+                bounds.min.z().min(point.z()),
+            ),
+            max: Point3(
+                bounds.max.x().max(point.x()),
+                bounds.max.y().max(point.y()),
+                bounds.max.z().max(point.z()),
+            ),
+        })
+    }
+
+    /// Construct the smallest bounding box containing both this and another box.
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb::from_points(&[self.min, self.max, other.min, other.max])
+    }
+
+    /// Construct the smallest bounding box containing a set of boxes.
+    ///
+    /// Returns `None` if there are no boxes.
+    pub fn surrounding_box(boxes: impl IntoIterator<Item = Aabb>) -> Option<Aabb> {
+        boxes.into_iter().reduce(|a, b| a.union(&b))
+    }
+
+    /// Grow the bounding box by a margin along each axis (in both directions).
+    pub fn expand(&self, margin: Vect3) -> Aabb {
+        Aabb {
+            min: self.min - margin,
+            max: self.max + margin,
+        }
+    }
+
+    /// Get the surface area of the bounding box.
+    pub fn area(&self) -> f32 {
+        let size = self.max - self.min;
+        2.0 * (size.x() * size.y() + size.y() * size.z() + size.z() * size.x())
+    }
+
+    /// Get the center point of the bounding box.
+    pub fn centroid(&self) -> Point3 {
+        self.min + 0.5 * (self.max - self.min)
+    }
+
+    /// Check whether a ray passes through the bounding box.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - ray to trace along
+    /// * `filter` - a distance range in which to intersect
+    pub fn hit(&self, ray: &Ray, filter: Range<f32>) -> bool {
+        let (enter, exit) = (0..3).fold((filter.start, filter.end), |(enter, exit), axis| {
+            let (origin, direction) = (ray.origin()[axis], ray.direction()[axis]);
+            let (lo, hi) = (self.min[axis], self.max[axis]);
+            if direction == 0.0 {
+                if (lo..=hi).contains(&origin) {
+                    (enter, exit)
+                } else {
+                    (f32::INFINITY, f32::NEG_INFINITY)
+                }
+            } else {
+                let t0 = (lo - origin) / direction;
+                let t1 = (hi - origin) / direction;
+                (enter.max(t0.min(t1)), exit.min(t0.max(t1)))
+            }
+        });
+        enter <= exit
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_from_points() {
+        let bounds = Aabb::from_points(&[Point3(1.0, -2.0, 0.0), Point3(-1.0, 3.0, 0.5)]);
+
+        assert_eq!(bounds.min, Point3(-1.0, -2.0, 0.0));
+        assert_eq!(bounds.max, Point3(1.0, 3.0, 0.5));
+        assert_eq!(bounds.centroid(), Point3(0.0, 0.5, 0.25));
+    }
+
+    #[test]
+    fn test_hit() {
+        let bounds = Aabb {
+            min: Point3(-1.0, -1.0, -1.0),
+            max: Point3(1.0, 1.0, 1.0),
+        };
+        let ray = Ray::new(Point3(0.5, 0.5, -5.0), Vect3(0.0, 0.0, 1.0));
+
+        assert!(bounds.hit(&ray, 0.0..f32::INFINITY));
+        assert!(!bounds.hit(&ray, 0.0..3.0));
+        assert!(!bounds.hit(&ray, 7.0..f32::INFINITY));
+    }
+
+    #[test]
+    fn test_miss_alongside() {
+        let bounds = Aabb {
+            min: Point3(-1.0, -1.0, -1.0),
+            max: Point3(1.0, 1.0, 1.0),
+        };
+        let parallel = Ray::new(Point3(1.5, 0.0, -5.0), Vect3(0.0, 0.0, 1.0));
+        let skewed = Ray::new(Point3(-5.0, 2.0, 0.0), Vect3(1.0, 0.1, 0.0));
+
+        assert!(!bounds.hit(&parallel, 0.0..f32::INFINITY));
+        assert!(!bounds.hit(&skewed, 0.0..f32::INFINITY));
+    }
+
+    #[test]
+    fn test_union() {
+        let a = Aabb {
+            min: Point3(-2.0, 0.0, 0.0),
+            max: Point3(-1.0, 1.0, 1.0),
+        };
+        let b = Aabb {
+            min: Point3(1.0, -1.0, 2.0),
+            max: Point3(3.0, 0.5, 4.0),
+        };
+        let expected = Aabb {
+            min: Point3(-2.0, -1.0, 0.0),
+            max: Point3(3.0, 1.0, 4.0),
+        };
+
+        assert_eq!(a.union(&b), expected);
+        assert_eq!(b.union(&a), expected);
+        assert_eq!(Aabb::surrounding_box([a, b]), Some(expected));
+        assert_eq!(Aabb::surrounding_box([a]), Some(a));
+        assert_eq!(Aabb::surrounding_box([]), None);
+    }
+}
@@ -0,0 +1,155 @@
+use super::{Aabb, Intersection, Surface};
+use crate::types::{Point3, Ray, Vect3};
+use std::cmp::Ordering;
+use std::ops::Range;
+
+/// An intersectable, finite cylinder closed by two end caps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cylinder {
+    pub base: Point3,
+    pub top: Point3,
+    pub radius: f32,
+}
+
+impl Cylinder {
+    /// Intersect the end cap centered on `center`, facing `normal`.
+    fn cap_distance(&self, ray: &Ray, center: Point3, normal: Vect3) -> Option<f32> {
+        let denominator = ray.direction().dot(normal);
+        if denominator == 0.0 {
+            return None;
+        }
+        let distance = (center - ray.origin()).dot(normal) / denominator;
+        let offset = ray.at(distance) - center;
+        if offset.dot(offset) <= self.radius * self.radius {
+            Some(distance)
+        } else {
+            None
+        }
+    }
+}
+
+impl Surface for Cylinder {
+    fn intersected_by(&self, ray: &Ray, filter: Range<f32>) -> Vec<Intersection> {
+        let axis = self.top - self.base;
+        let height = axis.norm();
+        let axis = axis / height;
+        // Solving ax² + 2bx + c = r² for the infinite cylinder, using only the
+        // components of the ray that are orthogonal to the cylinder axis.
+        let offset = ray.origin() - self.base;
+        let offset = offset - offset.dot(axis) * axis;
+        let direction = ray.direction() - ray.direction().dot(axis) * axis;
+        let a = direction.dot(direction);
+        let b = offset.dot(direction);
+        let c = offset.dot(offset) - (self.radius * self.radius);
+        let sides = [
+            (-b - ((b * b) - (a * c)).sqrt()) / a,
+            (-b + ((b * b) - (a * c)).sqrt()) / a,
+        ];
+        // Hits on the side are only valid between the two end caps.
+        let side_hits = IntoIterator::into_iter(sides)
+            .filter(|distance| filter.contains(distance))
+            .filter_map(|distance| {
+                let point = ray.at(distance);
+                let along = (point - self.base).dot(axis);
+                if (0.0..=height).contains(&along) {
+                    let normal = (point - self.base) - along * axis;
+                    Some((
+                        distance,
+                        Intersection::new(ray, point, normal / self.radius),
+                    ))
+                } else {
+                    None
+                }
+            });
+        // The caps are disks at either end of the cylinder.
+        let caps = [(self.base, -axis), (self.top, axis)];
+        let cap_hits = IntoIterator::into_iter(caps).filter_map(|(center, normal)| {
+            self.cap_distance(ray, center, normal)
+                .filter(|distance| filter.contains(distance))
+                .map(|distance| (distance, Intersection::new(ray, ray.at(distance), normal)))
+        });
+        let mut hits: Vec<(f32, Intersection)> = side_hits.chain(cap_hits).collect();
+        hits.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        hits.into_iter().map(|(_, hit)| hit).collect()
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        // Each end cap extends `radius * sin(angle)` from its center along
+        // every axis, where `angle` is the angle between the axis and the cap.
+        let axis = (self.top - self.base).normalize();
+        let extent = |a: f32| self.radius * (1.0 - a * a).max(0.0).sqrt();
+        Aabb::from_points(&[self.base, self.top]).expand(Vect3(
+            extent(axis.x()),
+            extent(axis.y()),
+            extent(axis.z()),
+        ))
+    }
+
+    fn area(&self) -> f32 {
+        let height = (self.top - self.base).norm();
+        2.0 * std::f32::consts::PI * self.radius * (height + self.radius)
+    }
+
+    fn contains_point(&self, point: Point3) -> bool {
+        let axis = self.top - self.base;
+        let offset = point - self.base;
+        let along = offset.dot(axis) / axis.dot(axis);
+        let radial = offset - along * axis;
+        (0.0..=1.0).contains(&along) && radial.dot(radial) < self.radius * self.radius
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use pretty_assertions::assert_eq;
+
+    fn cylinder() -> Cylinder {
+        Cylinder {
+            base: Point3(0.0, 0.0, 0.0),
+            top: Point3(0.0, 2.0, 0.0),
+            radius: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_side_intersection() {
+        let ray = Ray::new(Point3(0.0, 1.0, -5.0), Vect3(0.0, 0.0, 1.0));
+        let hits = cylinder().intersected_by(&ray, 0.0..f32::INFINITY);
+
+        assert_eq!(hits.len(), 2);
+        assert_abs_diff_eq!(hits[0].point(), Point3(0.0, 1.0, -1.0), epsilon = 1e-5);
+        assert_abs_diff_eq!(hits[0].normal(), Vect3(0.0, 0.0, -1.0), epsilon = 1e-5);
+        assert_abs_diff_eq!(hits[1].point(), Point3(0.0, 1.0, 1.0), epsilon = 1e-5);
+        assert_abs_diff_eq!(hits[1].normal(), Vect3(0.0, 0.0, -1.0), epsilon = 1e-5);
+        assert!(hits[0].front_face());
+        assert!(!hits[1].front_face());
+    }
+
+    #[test]
+    fn test_cap_intersection() {
+        let ray = Ray::new(Point3(0.5, 5.0, 0.0), Vect3(0.0, -1.0, 0.0));
+        let hits = cylinder().intersected_by(&ray, 0.0..f32::INFINITY);
+
+        let expected = vec![
+            Intersection::new(&ray, Point3(0.5, 2.0, 0.0), Vect3(0.0, 1.0, 0.0)),
+            Intersection::new(&ray, Point3(0.5, 0.0, 0.0), Vect3(0.0, -1.0, 0.0)),
+        ];
+        assert_eq!(hits, expected);
+    }
+
+    #[test]
+    fn test_contains_point() {
+        assert!(cylinder().contains_point(Point3(0.5, 1.0, 0.0)));
+        assert!(!cylinder().contains_point(Point3(0.5, 2.5, 0.0)));
+        assert!(!cylinder().contains_point(Point3(1.5, 1.0, 0.0)));
+    }
+
+    #[test]
+    fn test_no_intersection_above_top() {
+        let ray = Ray::new(Point3(-5.0, 3.0, 0.0), Vect3(1.0, 0.0, 0.0));
+
+        assert_eq!(cylinder().intersected_by(&ray, 0.0..f32::INFINITY), vec![]);
+    }
+}
@@ -0,0 +1,92 @@
+use super::{Aabb, Intersection, Surface};
+use crate::types::{Point3, Ray, Vect3};
+use std::ops::Range;
+
+/// An intersectable, flat disk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Disk {
+    pub center: Point3,
+    pub normal: Vect3,
+    pub radius: f32,
+}
+
+impl Surface for Disk {
+    fn intersected_by(&self, ray: &Ray, filter: Range<f32>) -> Vec<Intersection> {
+        // Intersect the plane supporting the disk. Rays parallel to the
+        // plane never intersect it (or lie in it, which we ignore).
+        let denominator = ray.direction().dot(self.normal);
+        if denominator == 0.0 {
+            return vec![];
+        }
+        let distance = (self.center - ray.origin()).dot(self.normal) / denominator;
+        let point = ray.at(distance);
+        let offset = point - self.center;
+        if !filter.contains(&distance) || offset.dot(offset) > self.radius * self.radius {
+            return vec![];
+        }
+        vec![Intersection::new(ray, point, self.normal)]
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let normal = self.normal.normalize();
+        let extent = |n: f32| self.radius * (1.0 - n * n).max(0.0).sqrt();
+        Aabb::from_points(&[self.center]).expand(Vect3(
+            extent(normal.x()),
+            extent(normal.y()),
+            extent(normal.z()),
+        ))
+    }
+
+    fn area(&self) -> f32 {
+        std::f32::consts::PI * self.radius * self.radius
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn disk() -> Disk {
+        Disk {
+            center: Point3(0.0, 0.0, 2.0),
+            normal: Vect3(0.0, 0.0, 1.0),
+            radius: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_centered_intersection() {
+        let ray = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
+
+        let expected = vec![Intersection::new(
+            &ray,
+            Point3(0.0, 0.0, 2.0),
+            Vect3(0.0, 0.0, 1.0),
+        )];
+        assert_eq!(expected[0].normal(), Vect3(0.0, 0.0, -1.0));
+        assert_eq!(disk().intersected_by(&ray, 0.0..f32::INFINITY), expected);
+    }
+
+    #[test]
+    fn test_intersection_inside_rim() {
+        let ray = Ray::new(Point3(0.0, 0.999, 4.0), Vect3(0.0, 0.0, -1.0));
+
+        let expected = vec![Intersection::new(
+            &ray,
+            Point3(0.0, 0.999, 2.0),
+            Vect3(0.0, 0.0, 1.0),
+        )];
+        assert_eq!(expected[0].normal(), Vect3(0.0, 0.0, 1.0));
+        assert_eq!(disk().intersected_by(&ray, 0.0..f32::INFINITY), expected);
+    }
+
+    #[test]
+    fn test_no_intersection_outside_rim() {
+        let ray = Ray::new(Point3(0.0, 1.001, 0.0), Vect3(0.0, 0.0, 1.0));
+        let parallel = Ray::new(Point3(0.0, 0.0, 2.0), Vect3(1.0, 0.0, 0.0));
+
+        assert_eq!(disk().intersected_by(&ray, 0.0..f32::INFINITY), vec![]);
+        assert_eq!(disk().intersected_by(&parallel, 0.0..f32::INFINITY), vec![]);
+    }
+}
@@ -0,0 +1,146 @@
+use super::sphere::spherical_uv;
+use super::{Aabb, Intersection, Surface};
+use crate::types::{Point3, Ray, Vect3};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use std::ops::Range;
+
+/// Divide a vector by another, component-wise.
+fn divide(a: Vect3, b: Vect3) -> Vect3 {
+    Vect3(a.x() / b.x(), a.y() / b.y(), a.z() / b.z())
+}
+
+/// An intersectable axis-aligned ellipsoid.
+///
+/// The ellipsoid is a unit sphere scaled by `radii` along each axis, and then
+/// moved to `center`. The radii should all be positive.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Ellipsoid {
+    pub center: Point3,
+    pub radii: Vect3,
+}
+
+impl Surface for Ellipsoid {
+    fn intersected_by(&self, ray: &Ray, filter: Range<f32>) -> Vec<Intersection> {
+        // Scale the ray into the space of a unit sphere at the origin. Since
+        // the direction is scaled (but not normalized), distances along the
+        // ray are the same in both spaces.
+        let offset = divide(ray.origin() - self.center, self.radii);
+        let direction = divide(ray.direction(), self.radii);
+        // Solving ax² + 2bx + c = 1, just as for a sphere.
+        let a = direction.dot(direction);
+        let b = offset.dot(direction);
+        let c = offset.dot(offset) - 1.0;
+        let distances = [
+            (-b - ((b * b) - (a * c)).sqrt()) / a,
+            (-b + ((b * b) - (a * c)).sqrt()) / a,
+        ];
+        IntoIterator::into_iter(distances)
+            .filter(|distance| filter.contains(distance))
+            .map(|distance| {
+                // Intersection! Scale the unit sphere normal back using the
+                // inverse transpose of the scaling, i.e. dividing by the radii.
+                let unit = offset + direction * distance;
+                let normal = divide(unit, self.radii);
+                Intersection::with_uv(ray, ray.at(distance), normal, spherical_uv(unit))
+            })
+            .collect()
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let radii = self.radii.abs();
+        Aabb {
+            min: self.center - radii,
+            max: self.center + radii,
+        }
+    }
+
+    fn area(&self) -> f32 {
+        // Knud Thomsen's approximation (with a relative error of at most 1.061%).
+        let p = 1.6075;
+        let (a, b, c) = (self.radii.x(), self.radii.y(), self.radii.z());
+        let mean = ((a * b).powf(p) + (a * c).powf(p) + (b * c).powf(p)) / 3.0;
+        4.0 * PI * mean.powf(p.recip())
+    }
+
+    fn contains_point(&self, point: Point3) -> bool {
+        divide(point - self.center, self.radii).norm_squared() < 1.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::surfaces::Sphere;
+    use approx::assert_abs_diff_eq;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_equal_radii() {
+        let sphere = Sphere {
+            center: Point3(0.5, 0.0, 2.0),
+            radius: 1.5,
+        };
+        let ellipsoid = Ellipsoid {
+            center: sphere.center,
+            radii: Vect3(1.5, 1.5, 1.5),
+        };
+        let ray = Ray::new(Point3(0.0, 0.3, -1.0), Vect3(0.2, 0.1, 1.0));
+
+        let expected = sphere.intersected_by(&ray, 0.0..f32::INFINITY);
+        let hits = ellipsoid.intersected_by(&ray, 0.0..f32::INFINITY);
+        assert_eq!(hits.len(), expected.len());
+        for (hit, expected) in hits.iter().zip(&expected) {
+            assert_abs_diff_eq!(hit.point(), expected.point(), epsilon = 1e-5);
+            assert_abs_diff_eq!(hit.normal(), expected.normal(), epsilon = 1e-5);
+            assert_eq!(hit.front_face(), expected.front_face());
+        }
+        assert_abs_diff_eq!(ellipsoid.area(), sphere.area(), epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_elongated() {
+        let ellipsoid = Ellipsoid {
+            center: Point3::zero(),
+            radii: Vect3(3.0, 1.0, 1.0),
+        };
+        let hit = |origin: Point3| {
+            let ray = Ray::new(origin, Point3::zero() - origin);
+            ellipsoid.intersected_by(&ray, 0.0..f32::INFINITY)[0]
+        };
+
+        // The silhouette is stretched along the x axis (but not the others).
+        assert_abs_diff_eq!(
+            hit(Point3(5.0, 0.0, 0.0)).point(),
+            Point3(3.0, 0.0, 0.0),
+            epsilon = 1e-5
+        );
+        assert_abs_diff_eq!(
+            hit(Point3(0.0, 5.0, 0.0)).point(),
+            Point3(0.0, 1.0, 0.0),
+            epsilon = 1e-5
+        );
+        assert_abs_diff_eq!(
+            hit(Point3(0.0, 0.0, 5.0)).point(),
+            Point3(0.0, 0.0, 1.0),
+            epsilon = 1e-5
+        );
+        let ray = Ray::new(Point3(2.0, 0.0, -5.0), Vect3(0.0, 0.0, 1.0));
+        assert_eq!(ellipsoid.intersected_by(&ray, 0.0..f32::INFINITY).len(), 2);
+        let ray = Ray::new(Point3(0.0, 2.0, -5.0), Vect3(0.0, 0.0, 1.0));
+        assert!(ellipsoid
+            .intersected_by(&ray, 0.0..f32::INFINITY)
+            .is_empty());
+
+        // The normal is perpendicular to the (flatter) surface.
+        let point = Point3(3.0 * 0.6, 0.8, 0.0);
+        let normal = hit(Point3::zero() + 2.0 * (point - Point3::zero())).normal();
+        assert_abs_diff_eq!(
+            normal,
+            Vect3(0.6 / 3.0, 0.8, 0.0).normalize(),
+            epsilon = 1e-5
+        );
+        assert!(ellipsoid.contains_point(Point3(2.5, 0.0, 0.0)));
+        assert!(!ellipsoid.contains_point(Point3(0.0, 1.5, 0.0)));
+    }
+}
@@ -0,0 +1,356 @@
+use super::triangle::intersect_triangle;
+use super::{Aabb, Intersection, Surface};
+use crate::types::{Point3, Ray, Vect3};
+use std::ops::Range;
+use std::{fs, io, path};
+
+/// An intersectable terrain defined by a regular grid of heights.
+///
+/// The grid lies in the xz-plane, starting at the origin and extending along
+/// the positive x and z axes. Each grid cell is split into two triangles, and
+/// normals are interpolated between the vertices for smooth shading.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heightfield {
+    columns: usize,
+    rows: usize,
+    scale: f32,
+    heights: Vec<f32>,
+    normals: Vec<Vect3>,
+    bounds: (f32, f32),
+}
+
+impl Heightfield {
+    /// Construct a heightfield from a grid of heights.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - number of samples along the x axis (at least two)
+    /// * `rows` - number of samples along the z axis (at least two)
+    /// * `heights` - heights in row-major order
+    /// * `scale` - distance between adjacent samples
+    /// * `height_scale` - factor applied to each height
+    pub fn new(
+        columns: usize,
+        rows: usize,
+        heights: Vec<f32>,
+        scale: f32,
+        height_scale: f32,
+    ) -> Heightfield {
+        assert!(
+            columns >= 2 && rows >= 2,
+            "Heightfield needs at least 2x2 samples"
+        );
+        assert_eq!(heights.len(), columns * rows, "Heightfield size mismatch");
+        let heights: Vec<f32> = heights.iter().map(|h| h * height_scale).collect();
+        // Approximate the normal at each sample using central differences
+        // (or one-sided differences along the edges of the grid).
+        let height = |i: usize, j: usize| heights[j * columns + i];
+        let normals = (0..rows)
+            .flat_map(|j| (0..columns).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let (x0, x1) = (i.saturating_sub(1), (i + 1).min(columns - 1));
+                let (z0, z1) = (j.saturating_sub(1), (j + 1).min(rows - 1));
+                let dx = (height(x1, j) - height(x0, j)) / (((x1 - x0) as f32) * scale);
+                let dz = (height(i, z1) - height(i, z0)) / (((z1 - z0) as f32) * scale);
+                Vect3(-dx, 1.0, -dz).normalize()
+            })
+            .collect();
+        let bounds = heights
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), h| {
+                (lo.min(*h), hi.max(*h))
+            });
+        Heightfield {
+            columns,
+            rows,
+            scale,
+            heights,
+            normals,
+            bounds,
+        }
+    }
+
+    /// Load a heightfield from a PNG image.
+    ///
+    /// Each pixel of the image becomes a sample of the heightfield, with the
+    /// brightness of the pixel (in the range `0.0` to `1.0`) as its height.
+    /// Returns an `InvalidData` error if the image is less than two pixels
+    /// wide or high, since that does not span any grid cells.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path to a PNG image
+    /// * `scale` - distance between adjacent samples
+    /// * `height_scale` - height of a white pixel
+    pub fn from_image<P: AsRef<path::Path>>(
+        path: P,
+        scale: f32,
+        height_scale: f32,
+    ) -> Result<Heightfield, io::Error> {
+        let mut decoder = png::Decoder::new(fs::File::open(path)?);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+        if info.width < 2 || info.height < 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Heightmap must be at least 2x2 pixels, not {}x{}",
+                    info.width, info.height
+                ),
+            ));
+        }
+        // Only use the color channels (i.e. skip alpha), and average them.
+        let channels = info.color_type.samples();
+        let colors = if channels > 2 { 3 } else { 1 };
+        let heights = buffer[..info.buffer_size()]
+            .chunks_exact(channels)
+            .map(|pixel| {
+                let sum: f32 = pixel[..colors].iter().map(|c| *c as f32).sum();
+                sum / (255.0 * (colors as f32))
+            })
+            .collect();
+        Ok(Heightfield::new(
+            info.width as usize,
+            info.height as usize,
+            heights,
+            scale,
+            height_scale,
+        ))
+    }
+
+    /// Get the position of a grid sample.
+    fn vertex(&self, i: usize, j: usize) -> Point3 {
+        let height = self.heights[j * self.columns + i];
+        Point3((i as f32) * self.scale, height, (j as f32) * self.scale)
+    }
+
+    /// Get the normal of a grid sample.
+    fn normal(&self, i: usize, j: usize) -> Vect3 {
+        self.normals[j * self.columns + i]
+    }
+
+    /// Compute the distances at which a ray enters and exits the grid bounds.
+    fn clip(&self, ray: &Ray) -> Option<(f32, f32)> {
+        let max_x = ((self.columns - 1) as f32) * self.scale;
+        let max_z = ((self.rows - 1) as f32) * self.scale;
+        let slabs = [
+            (ray.origin().x(), ray.direction().x(), 0.0, max_x),
+            (
+                ray.origin().y(),
+                ray.direction().y(),
+                self.bounds.0,
+                self.bounds.1,
+            ),
+            (ray.origin().z(), ray.direction().z(), 0.0, max_z),
+        ];
+        let (enter, exit) = slabs.iter().fold(
+            (f32::NEG_INFINITY, f32::INFINITY),
+            |(enter, exit), (origin, direction, lo, hi)| {
+                if *direction == 0.0 {
+                    if (*lo..=*hi).contains(origin) {
+                        (enter, exit)
+                    } else {
+                        (f32::INFINITY, f32::NEG_INFINITY)
+                    }
+                } else {
+                    let t0 = (lo - origin) / direction;
+                    let t1 = (hi - origin) / direction;
+                    (enter.max(t0.min(t1)), exit.min(t0.max(t1)))
+                }
+            },
+        );
+        if enter <= exit {
+            Some((enter, exit))
+        } else {
+            None
+        }
+    }
+
+    /// Intersect the two triangles of a single grid cell.
+    fn intersect_cell(&self, ray: &Ray, i: usize, j: usize) -> Vec<(f32, Intersection)> {
+        let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+        let triangles = [
+            [corners[0], corners[1], corners[2]],
+            [corners[0], corners[2], corners[3]],
+        ];
+        triangles
+            .iter()
+            .filter_map(|triangle| {
+                let vertices = triangle.map(|(i, j)| self.vertex(i, j));
+                let (distance, u, v) = intersect_triangle(ray, vertices)?;
+                let normals = triangle.map(|(i, j)| self.normal(i, j));
+                let normal = (1.0 - u - v) * normals[0] + u * normals[1] + v * normals[2];
+                Some((distance, Intersection::new(ray, ray.at(distance), normal)))
+            })
+            .collect()
+    }
+}
+
+impl Surface for Heightfield {
+    fn intersected_by(&self, ray: &Ray, filter: Range<f32>) -> Vec<Intersection> {
+        let (enter, exit) = match self.clip(ray) {
+            Some(range) => range,
+            None => return vec![],
+        };
+        // Walk the grid cells along the ray (projected onto the xz-plane) using
+        // a digital differential analyzer, testing each visited cell.
+        let start = ray.at(enter);
+        let cell =
+            |coord: f32, cells: usize| ((coord / self.scale).max(0.0) as usize).min(cells - 2);
+        let (mut i, mut j) = (cell(start.x(), self.columns), cell(start.z(), self.rows));
+        let axis = |origin: f32, direction: f32, index: usize| {
+            if direction == 0.0 {
+                (0, f32::INFINITY, f32::INFINITY)
+            } else {
+                let step = if direction > 0.0 { 1 } else { -1 };
+                let boundary = ((index as isize) + (step + 1) / 2) as f32 * self.scale;
+                let next = (boundary - origin) / direction;
+                (step, next, self.scale / direction.abs())
+            }
+        };
+        let (step_x, mut next_x, delta_x) = axis(ray.origin().x(), ray.direction().x(), i);
+        let (step_z, mut next_z, delta_z) = axis(ray.origin().z(), ray.direction().z(), j);
+        let tolerance = 1e-4 * self.scale;
+        let mut cell_start = enter;
+        let mut hits: Vec<(f32, Intersection)> = vec![];
+        loop {
+            let cell_end = next_x.min(next_z).min(exit);
+            for (distance, hit) in self.intersect_cell(ray, i, j) {
+                let in_cell = (cell_start - tolerance..=cell_end + tolerance).contains(&distance);
+                let duplicate = hits
+                    .last()
+                    .is_some_and(|(last, _)| (last - distance).abs() < tolerance);
+                if in_cell && !duplicate && filter.contains(&distance) {
+                    hits.push((distance, hit));
+                }
+            }
+            if cell_end >= exit {
+                break;
+            }
+            let (index, limit, step) = if next_x < next_z {
+                next_x += delta_x;
+                (&mut i, self.columns - 1, step_x)
+            } else {
+                next_z += delta_z;
+                (&mut j, self.rows - 1, step_z)
+            };
+            match index.checked_add_signed(step) {
+                Some(next) if next < limit => *index = next,
+                _ => break,
+            }
+            cell_start = cell_end;
+        }
+        hits.into_iter().map(|(_, hit)| hit).collect()
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let max_x = ((self.columns - 1) as f32) * self.scale;
+        let max_z = ((self.rows - 1) as f32) * self.scale;
+        Aabb {
+            min: Point3(0.0, self.bounds.0, 0.0),
+            max: Point3(max_x, self.bounds.1, max_z),
+        }
+    }
+
+    fn area(&self) -> f32 {
+        (0..self.rows - 1)
+            .flat_map(|j| (0..self.columns - 1).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let corners = [
+                    self.vertex(i, j),
+                    self.vertex(i + 1, j),
+                    self.vertex(i + 1, j + 1),
+                    self.vertex(i, j + 1),
+                ];
+                let diagonal = corners[2] - corners[0];
+                let first = (corners[1] - corners[0]).cross(diagonal).norm();
+                let second = diagonal.cross(corners[3] - corners[0]).norm();
+                0.5 * (first + second)
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_flat_heightmap_from_image() -> Result<(), io::Error> {
+        let path = std::env::temp_dir().join("raytrust_test_flat_heightmap.png");
+        {
+            let file = fs::File::create(&path)?;
+            let mut encoder = png::Encoder::new(file, 4, 4);
+            encoder.set_color(png::ColorType::Grayscale);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&[51; 16])?;
+        }
+        let heightfield = Heightfield::from_image(&path, 1.0, 5.0)?;
+        fs::remove_file(&path)?;
+
+        let ray = Ray::new(Point3(1.3, 10.0, 2.6), Vect3(0.0, -1.0, 0.0));
+        let hits = heightfield.intersected_by(&ray, 0.0..f32::INFINITY);
+        assert_eq!(hits.len(), 1);
+        assert_abs_diff_eq!(hits[0].point(), Point3(1.3, 1.0, 2.6), epsilon = 1e-5);
+        assert_abs_diff_eq!(hits[0].normal(), Vect3(0.0, 1.0, 0.0), epsilon = 1e-5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_narrow_heightmap_from_image() -> Result<(), io::Error> {
+        let path = std::env::temp_dir().join("raytrust_test_narrow_heightmap.png");
+        {
+            let file = fs::File::create(&path)?;
+            let mut encoder = png::Encoder::new(file, 1, 4);
+            encoder.set_color(png::ColorType::Grayscale);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&[51; 4])?;
+        }
+        let error = Heightfield::from_image(&path, 1.0, 5.0).unwrap_err();
+        fs::remove_file(&path)?;
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sloped_heightfield() {
+        // A ramp rising along the x axis, hit from above by a ray crossing cells.
+        let heights = vec![0.0, 1.0, 2.0, 3.0, 0.0, 1.0, 2.0, 3.0];
+        let heightfield = Heightfield::new(4, 2, heights, 1.0, 1.0);
+        let ray = Ray::new(Point3(0.0, 2.5, 0.5), Vect3(1.0, -1.0, 0.0));
+
+        let hits = heightfield.intersected_by(&ray, 0.0..f32::INFINITY);
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].front_face());
+        assert_abs_diff_eq!(hits[0].point(), Point3(1.25, 1.25, 0.5), epsilon = 1e-5);
+        assert_abs_diff_eq!(
+            hits[0].normal(),
+            Vect3(-1.0, 1.0, 0.0).normalize(),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn test_no_intersection_outside_grid() {
+        let heightfield = Heightfield::new(2, 2, vec![0.0; 4], 1.0, 1.0);
+        let ray = Ray::new(Point3(2.0, 10.0, 0.5), Vect3(0.0, -1.0, 0.0));
+
+        assert_eq!(heightfield.intersected_by(&ray, 0.0..f32::INFINITY), vec![]);
+    }
+
+    #[test]
+    fn test_area() {
+        let flat = Heightfield::new(3, 2, vec![1.0; 6], 0.5, 1.0);
+        let ramp = Heightfield::new(2, 2, vec![0.0, 1.0, 0.0, 1.0], 1.0, 1.0);
+
+        assert_abs_diff_eq!(flat.area(), 0.5, epsilon = 1e-6);
+        assert_abs_diff_eq!(ramp.area(), 2.0_f32.sqrt(), epsilon = 1e-6);
+    }
+}
@@ -0,0 +1,107 @@
+use super::{Aabb, Intersection, Surface};
+use crate::sampler;
+use crate::types::{Point3, Ray};
+use rand::Rng;
+use rand_distr::Uniform;
+use std::ops::Range;
+
+/// A volume of constant density (e.g. smoke or fog), enclosed by a boundary.
+///
+/// Rays passing through the volume may scatter at any point inside it, with
+/// a probability proportional to the density of the medium. The boundary
+/// should be a closed surface.
+pub struct ConstantMedium {
+    pub boundary: Box<dyn Surface>,
+    pub density: f32,
+}
+
+impl Surface for ConstantMedium {
+    fn intersected_by(&self, ray: &Ray, filter: Range<f32>) -> Vec<Intersection> {
+        // Find the distances at which the ray enters and exits the volume.
+        let scale = ray.direction().norm();
+        let hits = self.boundary.intersected_by(ray, filter.clone());
+        let mut distances = hits.iter().map(|hit| hit.distance() / scale);
+        let (enter, exit) = if self.boundary.contains_point(ray.origin()) {
+            (Some(filter.start), distances.next())
+        } else {
+            (distances.next(), distances.next())
+        };
+        let (enter, exit) = match (enter, exit) {
+            (Some(enter), Some(exit)) => (enter, exit),
+            _ => return vec![],
+        };
+        // Pick a random scattering distance, and check that it's inside.
+        let mut rng = sampler::rng();
+        let random: f32 = rng.sample(Uniform::new(f32::EPSILON, 1.0));
+        let scattering = -random.ln() / self.density;
+        let distance = enter + scattering / scale;
+        if distance >= exit {
+            return vec![];
+        }
+        // The normal is arbitrary, since the medium has no surface.
+        vec![Intersection::new(ray, ray.at(distance), -ray.direction())]
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.boundary.bounding_box()
+    }
+
+    fn area(&self) -> f32 {
+        self.boundary.area()
+    }
+
+    fn contains_point(&self, point: Point3) -> bool {
+        self.boundary.contains_point(point)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::surfaces::Sphere;
+    use crate::types::Vect3;
+    use pretty_assertions::assert_eq;
+
+    fn medium(density: f32) -> ConstantMedium {
+        ConstantMedium {
+            boundary: Box::new(Sphere {
+                center: Point3::zero(),
+                radius: 10.0,
+            }),
+            density,
+        }
+    }
+
+    #[test]
+    fn test_denser_medium_scatters_sooner() {
+        let ray = Ray::new(Point3(0.0, 0.0, -20.0), Vect3(0.0, 0.0, 1.0));
+        let mean_distance = |medium: &ConstantMedium| {
+            let distances: Vec<f32> = (0..1000)
+                .flat_map(|_| medium.intersected_by(&ray, 0.0..f32::INFINITY))
+                .map(|hit| hit.distance() - 10.0)
+                .collect();
+            distances.iter().sum::<f32>() / (distances.len() as f32)
+        };
+
+        let thin = mean_distance(&medium(0.1));
+        let dense = mean_distance(&medium(1.0));
+        assert!(dense < thin);
+        assert!((dense - 1.0).abs() < 0.2);
+    }
+
+    #[test]
+    fn test_ray_inside_medium() {
+        let ray = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
+        let hits = medium(100.0).intersected_by(&ray, 0.0..f32::INFINITY);
+
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].distance() < 1.0);
+    }
+
+    #[test]
+    fn test_ray_missing_medium() {
+        let ray = Ray::new(Point3(0.0, 20.0, -20.0), Vect3(0.0, 0.0, 1.0));
+
+        assert_eq!(medium(1.0).intersected_by(&ray, 0.0..f32::INFINITY), vec![]);
+    }
+}
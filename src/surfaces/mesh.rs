@@ -0,0 +1,152 @@
+use super::Triangle;
+use crate::types::Point3;
+use std::path::Path;
+use std::{error, fmt, fs};
+
+/// An error occurring when loading a mesh.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MeshError {
+    /// The mesh file could not be read.
+    Io(String),
+    /// A line of the mesh file could not be parsed.
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::Io(error) => write!(f, "Could not read mesh file: {}", error),
+            MeshError::Parse { line, message } => {
+                write!(f, "Invalid mesh file (line {}): {}", line, message)
+            }
+        }
+    }
+}
+
+impl error::Error for MeshError {}
+
+/// Load a triangle mesh from a Wavefront OBJ file.
+///
+/// See `parse_obj` for the supported subset of the format.
+///
+/// # Arguments
+///
+/// * `path` - path to the OBJ file
+pub fn load_obj(path: impl AsRef<Path>) -> Result<Vec<Triangle>, MeshError> {
+    let obj = fs::read_to_string(path).map_err(|error| MeshError::Io(error.to_string()))?;
+    parse_obj(&obj)
+}
+
+/// Parse a triangle mesh from the contents of a Wavefront OBJ file.
+///
+/// Only vertices (`v`) and faces (`f`) are used; every other statement
+/// (including vertex normals and texture coordinates) is ignored. Polygons
+/// are triangulated as a fan around their first vertex.
+///
+/// # Arguments
+///
+/// * `obj` - the contents of the OBJ file
+pub fn parse_obj(obj: &str) -> Result<Vec<Triangle>, MeshError> {
+    let mut vertices = vec![];
+    let mut triangles = vec![];
+    for (index, line) in obj.lines().enumerate() {
+        let error = |message: &str| MeshError::Parse {
+            line: index + 1,
+            message: message.to_string(),
+        };
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let coordinates = tokens
+                    .take(3)
+                    .map(|token| token.parse::<f32>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| error("invalid vertex coordinate"))?;
+                match coordinates[..] {
+                    [x, y, z] => vertices.push(Point3(x, y, z)),
+                    _ => return Err(error("vertex has fewer than three coordinates")),
+                }
+            }
+            Some("f") => {
+                let face = tokens
+                    .map(|token| {
+                        // Only the vertex index (before any `/`) is used.
+                        let index = token.split('/').next().unwrap_or_default();
+                        let index = index
+                            .parse::<isize>()
+                            .map_err(|_| error("invalid vertex index"))?;
+                        // Indices are 1-based, or relative to the end if negative.
+                        let resolved = match index {
+                            i if i > 0 => (i - 1) as usize,
+                            i if i < 0 => vertices.len().wrapping_sub(i.unsigned_abs()),
+                            _ => usize::MAX,
+                        };
+                        vertices
+                            .get(resolved)
+                            .copied()
+                            .ok_or_else(|| error("vertex index out of range"))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if face.len() < 3 {
+                    return Err(error("face has fewer than three vertices"));
+                }
+                triangles.extend(
+                    face.windows(2)
+                        .skip(1)
+                        .map(|edge| Triangle::new(face[0], edge[0], edge[1])),
+                );
+            }
+            _ => {}
+        }
+    }
+    Ok(triangles)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_quad() {
+        let obj = indoc::indoc! {"
+            # A unit quad.
+            v 0.0 0.0 0.0
+            v 1.0 0.0 0.0
+            v 1.0 1.0 0.0
+            v 0.0 1.0 0.0
+            vn 0.0 0.0 1.0
+            f 1//1 2//1 3//1 -1//1
+        "};
+
+        let (a, b, c, d) = (
+            Point3(0.0, 0.0, 0.0),
+            Point3(1.0, 0.0, 0.0),
+            Point3(1.0, 1.0, 0.0),
+            Point3(0.0, 1.0, 0.0),
+        );
+        assert_eq!(
+            parse_obj(obj).unwrap(),
+            vec![Triangle::new(a, b, c), Triangle::new(a, c, d)]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_obj("v 0.0 0.0\n"),
+            Err(MeshError::Parse {
+                line: 1,
+                message: String::from("vertex has fewer than three coordinates"),
+            })
+        );
+        assert_eq!(
+            parse_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n"),
+            Err(MeshError::Parse {
+                line: 4,
+                message: String::from("vertex index out of range"),
+            })
+        );
+        assert!(load_obj("missing.obj").is_err());
+    }
+}
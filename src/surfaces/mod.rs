@@ -1,22 +1,67 @@
 /// Surfaces forming part of a renderable scene.
+mod aabb;
+mod cylinder;
+mod disk;
+mod ellipsoid;
+mod heightfield;
+mod medium;
+mod mesh;
 mod sphere;
+mod transformed;
+mod triangle;
 
 // Exports.
-pub use sphere::Sphere;
+pub use aabb::Aabb;
+pub use cylinder::Cylinder;
+pub use disk::Disk;
+pub use ellipsoid::Ellipsoid;
+pub use heightfield::Heightfield;
+pub use medium::ConstantMedium;
+pub use mesh::{load_obj, parse_obj, MeshError};
+pub use sphere::{MovingSphere, Sphere};
+pub use transformed::Transformed;
+pub use triangle::Triangle;
 
 // Imports.
 use crate::types::{Point3, Ray, Vect3};
 use std::ops::Range;
 
 /// An intersectable surface.
-pub trait Surface {
+///
+/// Surfaces are shared between rendering threads, so must be `Send + Sync`.
+pub trait Surface: Send + Sync {
     /// Return all intersectiona between a ray and this surface.
     ///
+    /// Only intersections in front of the ray origin (within `filter`) are
+    /// returned, so a ray originating inside a closed surface only intersects
+    /// it where it exits the surface.
+    ///
     /// # Arguments
     ///
     /// * `ray` - ray to trace along
     /// * `filter` - a distance range in which to intersect
     fn intersected_by(&self, ray: &Ray, filter: Range<f32>) -> Vec<Intersection>;
+
+    /// Compute an axis-aligned box bounding this surface.
+    ///
+    /// Unbounded surfaces should return an infinitely large box.
+    fn bounding_box(&self) -> Aabb;
+
+    /// Compute the total area of this surface.
+    ///
+    /// Unbounded surfaces should return `f32::INFINITY`.
+    fn area(&self) -> f32;
+
+    /// Check whether a point lies inside the volume enclosed by this surface.
+    ///
+    /// Open surfaces do not enclose any volume, which is the default.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - the point to check
+    fn contains_point(&self, _point: Point3) -> bool {
+        false
+    }
 }
 
 /// An intersection.
@@ -24,14 +69,38 @@ pub trait Surface {
 pub struct Intersection {
     point: Point3,
     normal: Vect3,
+    distance_squared: f32,
+    front_face: bool,
+    uv: (f32, f32),
 }
 
 impl Intersection {
     /// Construct an intersection.
-    pub fn new(point: Point3, normal: Vect3) -> Intersection {
+    ///
+    /// The given normal should be the geometric (outward) normal of the surface.
+    /// It is compared against the direction of the ray to determine whether the
+    /// ray hit the front or the back of the surface, and is then stored flipped
+    /// (if needed) so that it always opposes the incoming ray.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - the incoming ray
+    /// * `point` - the point of intersection
+    /// * `normal` - the outward normal of the surface at the point
+    pub fn new(ray: &Ray, point: Point3, normal: Vect3) -> Intersection {
+        Intersection::with_uv(ray, point, normal, (0.0, 0.0))
+    }
+
+    /// Construct an intersection with texture coordinates.
+    pub fn with_uv(ray: &Ray, point: Point3, normal: Vect3, uv: (f32, f32)) -> Intersection {
+        let normal = normal.normalize();
+        let front_face = ray.direction().dot(normal) < 0.0;
         Intersection {
             point,
-            normal: normal.normalize(),
+            normal: if front_face { normal } else { -normal },
+            distance_squared: (point - ray.origin()).norm_squared(),
+            front_face,
+            uv,
         }
     }
 
@@ -43,4 +112,26 @@ impl Intersection {
     pub fn normal(&self) -> Vect3 {
         self.normal
     }
+
+    /// Get the distance from the ray origin to this intersection.
+    pub fn distance(&self) -> f32 {
+        self.distance_squared.sqrt()
+    }
+
+    /// Get the squared distance from the ray origin to this intersection.
+    ///
+    /// This is cheaper than `distance`, and sufficient for ordering hits.
+    pub fn distance_squared(&self) -> f32 {
+        self.distance_squared
+    }
+
+    /// Check whether the ray hit the front (outside) of the surface.
+    pub fn front_face(&self) -> bool {
+        self.front_face
+    }
+
+    /// Get the texture coordinates of this intersection.
+    pub fn uv(&self) -> (f32, f32) {
+        self.uv
+    }
 }
@@ -1,14 +1,53 @@
-use super::{Intersection, Surface};
-use crate::types::{Point3, Ray};
+use super::{Aabb, Intersection, Surface};
+use crate::types::{Point3, Ray, Vect3};
+use rand::Rng;
+use rand_distr::UnitSphere;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
 use std::ops::Range;
 
+/// Compute the texture coordinates of a point on a unit sphere.
+///
+/// The `u` coordinate is the longitude around the y axis, starting at
+/// `-x`, while `v` is the latitude, from `0.0` at `-y` to `1.0` at `+y`.
+pub(super) fn spherical_uv(point: Vect3) -> (f32, f32) {
+    let theta = (-point.y()).clamp(-1.0, 1.0).acos();
+    let phi = (-point.z()).atan2(point.x()) + PI;
+    (phi / (2.0 * PI), theta / PI)
+}
+
 /// An intersectable sphere.
-#[derive(Debug, Clone, Copy, PartialEq)]
+///
+/// A sphere with a negative radius is inside-out, i.e. its normals point
+/// inwards and it contains every point outside of it. It is intersected at
+/// the same points as the sphere with the corresponding positive radius, but
+/// rays hitting it from the outside hit its back face (and vice versa). This
+/// allows a negative-radius sphere placed inside a dielectric sphere to model
+/// a hollow glass shell, since rays refract into the air when entering it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Sphere {
     pub center: Point3,
     pub radius: f32,
 }
 
+impl Sphere {
+    /// Pick a uniformly distributed random point on the surface of the sphere.
+    ///
+    /// Returns the point, the outward normal at the point, and the probability
+    /// density of picking it (with respect to surface area).
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - random number generator to sample with
+    pub fn sample_surface(&self, rng: &mut impl Rng) -> (Point3, Vect3, f32) {
+        let [x, y, z]: [f32; 3] = rng.sample(UnitSphere);
+        let direction = Vect3(x, y, z);
+        let point = self.center + direction * self.radius.abs();
+        let normal = direction * self.radius.signum();
+        (point, normal, self.area().recip())
+    }
+}
+
 impl Surface for Sphere {
     fn intersected_by(&self, ray: &Ray, filter: Range<f32>) -> Vec<Intersection> {
         let offset = ray.origin() - self.center;
@@ -27,20 +66,82 @@ impl Surface for Sphere {
         IntoIterator::into_iter(distances)
             .filter(|distance| filter.contains(distance))
             .map(|distance| {
-                // Intersection! Return a point and normal.
+                // Intersection! Return a point, normal and texture coordinates.
                 let point = ray.at(distance);
                 let normal = point - self.center;
-                Intersection::new(point, normal / self.radius)
+                let uv = spherical_uv(normal / self.radius.abs());
+                Intersection::with_uv(ray, point, normal / self.radius, uv)
             })
             .collect()
     }
+
+    fn bounding_box(&self) -> Aabb {
+        let radius = self.radius.abs();
+        Aabb {
+            min: self.center - Vect3(radius, radius, radius),
+            max: self.center + Vect3(radius, radius, radius),
+        }
+    }
+
+    fn area(&self) -> f32 {
+        4.0 * PI * self.radius * self.radius
+    }
+
+    fn contains_point(&self, point: Point3) -> bool {
+        let offset = point - self.center;
+        (offset.dot(offset) < self.radius * self.radius) == (self.radius > 0.0)
+    }
+}
+
+/// An intersectable sphere moving along a straight line.
+///
+/// The center of the sphere moves linearly from `start` at time `0.0` to
+/// `end` at time `1.0`, and is intersected at the time of the ray. Rendering
+/// it with a camera whose shutter is open during this interval causes motion
+/// blur. Outside of this interval the sphere is at rest, at `start` before
+/// time `0.0` and at `end` after time `1.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MovingSphere {
+    pub start: Point3,
+    pub end: Point3,
+    pub radius: f32,
+}
+
+impl MovingSphere {
+    /// Get the (stationary) sphere at a specific point in time.
+    pub fn at(&self, time: f32) -> Sphere {
+        Sphere {
+            center: self.start.lerp(self.end, time.clamp(0.0, 1.0)),
+            radius: self.radius,
+        }
+    }
+}
+
+impl Surface for MovingSphere {
+    fn intersected_by(&self, ray: &Ray, filter: Range<f32>) -> Vec<Intersection> {
+        self.at(ray.time()).intersected_by(ray, filter)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        // The sphere only moves during `[0, 1]`, so this bounds it at any time.
+        self.at(0.0)
+            .bounding_box()
+            .union(&self.at(1.0).bounding_box())
+    }
+
+    fn area(&self) -> f32 {
+        self.at(0.0).area()
+    }
+
+    fn contains_point(&self, point: Point3) -> bool {
+        self.at(0.0).contains_point(point)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::types::Vect3;
-
     use super::*;
+    use approx::assert_abs_diff_eq;
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -65,8 +166,18 @@ mod test {
         let ray = Ray::new(Point3(1.0, 0.0, 0.0), Vect3(0.0, 0.0, 1.0));
 
         let expected = vec![
-            Intersection::new(Point3(1.0, 0.0, 2.0), Vect3(1.0, 0.0, 0.0)),
-            Intersection::new(Point3(1.0, 0.0, 2.0), Vect3(1.0, 0.0, 0.0)),
+            Intersection::with_uv(
+                &ray,
+                Point3(1.0, 0.0, 2.0),
+                Vect3(1.0, 0.0, 0.0),
+                (0.5, 0.5),
+            ),
+            Intersection::with_uv(
+                &ray,
+                Point3(1.0, 0.0, 2.0),
+                Vect3(1.0, 0.0, 0.0),
+                (0.5, 0.5),
+            ),
         ];
         assert_eq!(sphere.intersected_by(&ray, 0.0..f32::INFINITY), expected);
     }
@@ -80,9 +191,185 @@ mod test {
         let ray = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
 
         let expected = vec![
-            Intersection::new(Point3(0.0, 0.0, 1.0), Vect3(0.0, 0.0, -1.0)),
-            Intersection::new(Point3(0.0, 0.0, 3.0), Vect3(0.0, 0.0, 1.0)),
+            Intersection::with_uv(
+                &ray,
+                Point3(0.0, 0.0, 1.0),
+                Vect3(0.0, 0.0, -1.0),
+                (0.75, 0.5),
+            ),
+            Intersection::with_uv(
+                &ray,
+                Point3(0.0, 0.0, 3.0),
+                Vect3(0.0, 0.0, 1.0),
+                (0.25, 0.5),
+            ),
         ];
         assert_eq!(sphere.intersected_by(&ray, 0.0..f32::INFINITY), expected);
     }
+
+    #[test]
+    fn test_spherical_uv() {
+        let uv = |point: Vect3| {
+            let sphere = Sphere {
+                center: Point3::zero(),
+                radius: 1.0,
+            };
+            let ray = Ray::new(Point3::zero() + 2.0 * point, -point);
+            sphere.intersected_by(&ray, 0.0..f32::INFINITY)[0].uv()
+        };
+
+        assert_abs_diff_eq!(uv(Vect3(0.0, 1.0, 0.0)).1, 1.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(uv(Vect3(0.0, -1.0, 0.0)).1, 0.0, epsilon = 1e-6);
+        let equator = [
+            (Vect3(1.0, 0.0, 0.0), 0.5),
+            (Vect3(0.0, 0.0, 1.0), 0.25),
+            (Vect3(0.0, 0.0, -1.0), 0.75),
+        ];
+        for (point, u) in equator {
+            assert_abs_diff_eq!(uv(point).0, u, epsilon = 1e-6);
+            assert_abs_diff_eq!(uv(point).1, 0.5, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_front_face() {
+        let sphere = Sphere {
+            center: Point3(0.0, 0.0, 2.0),
+            radius: 1.0,
+        };
+        let outside = Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0));
+        let inside = Ray::new(Point3(0.0, 0.0, 2.0), Vect3(0.0, 0.0, 1.0));
+
+        let hits = sphere.intersected_by(&outside, 0.0..f32::INFINITY);
+        assert!(hits[0].front_face());
+        assert_eq!(hits[0].normal(), Vect3(0.0, 0.0, -1.0));
+        let hits = sphere.intersected_by(&inside, 0.0..f32::INFINITY);
+        assert_eq!(hits.len(), 1);
+        assert!(!hits[0].front_face());
+        assert_eq!(hits[0].normal(), Vect3(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn test_negative_radius() {
+        let solid = Sphere {
+            center: Point3(0.0, 0.0, 2.0),
+            radius: 1.0,
+        };
+        let hollow = Sphere {
+            radius: -1.0,
+            ..solid
+        };
+        let ray = Ray::new(Point3(0.0, 0.5, 0.0), Vect3(0.0, 0.0, 1.0));
+
+        let solid_hits = solid.intersected_by(&ray, 0.0..f32::INFINITY);
+        let hollow_hits = hollow.intersected_by(&ray, 0.0..f32::INFINITY);
+        assert_eq!(hollow_hits.len(), 2);
+        for (solid_hit, hollow_hit) in solid_hits.iter().zip(&hollow_hits) {
+            assert_eq!(hollow_hit.point(), solid_hit.point());
+            assert_eq!(hollow_hit.normal(), solid_hit.normal());
+            assert_eq!(hollow_hit.front_face(), !solid_hit.front_face());
+            // The outward normal of the hollow sphere points towards its center.
+            let outward = if hollow_hit.front_face() {
+                hollow_hit.normal()
+            } else {
+                -hollow_hit.normal()
+            };
+            assert!(outward.dot(hollow.center - hollow_hit.point()) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_ray_from_center() {
+        let sphere = Sphere {
+            center: Point3(0.0, 0.0, 2.0),
+            radius: 1.0,
+        };
+        let ray = Ray::new(sphere.center, Vect3(1.0, 0.0, 0.0));
+
+        let hits = sphere.intersected_by(&ray, 0.0..f32::INFINITY);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].point(), Point3(1.0, 0.0, 2.0));
+        assert!(!hits[0].front_face());
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let sphere = Sphere {
+            center: Point3(0.0, 0.0, 2.0),
+            radius: 1.0,
+        };
+        let hollow = Sphere {
+            center: Point3(0.0, 0.0, 2.0),
+            radius: -1.0,
+        };
+
+        assert!(sphere.contains_point(Point3(0.0, 0.5, 2.0)));
+        assert!(!sphere.contains_point(Point3::zero()));
+        assert!(!hollow.contains_point(Point3(0.0, 0.5, 2.0)));
+        assert!(hollow.contains_point(Point3::zero()));
+    }
+
+    #[test]
+    fn test_area() {
+        let sphere = Sphere {
+            center: Point3(0.0, 0.0, 2.0),
+            radius: 1.0,
+        };
+        let hollow = Sphere {
+            center: Point3(0.0, 0.0, 2.0),
+            radius: -2.0,
+        };
+
+        assert_eq!(sphere.area(), 4.0 * std::f32::consts::PI);
+        assert_eq!(hollow.area(), 16.0 * std::f32::consts::PI);
+    }
+
+    #[test]
+    fn test_sample_surface() {
+        let sphere = Sphere {
+            center: Point3(1.0, 2.0, 3.0),
+            radius: 2.0,
+        };
+        let mut rng = rand::thread_rng();
+        let samples: Vec<_> = (0..10000)
+            .map(|_| sphere.sample_surface(&mut rng))
+            .collect();
+
+        for (point, normal, pdf) in &samples {
+            assert_abs_diff_eq!((*point - sphere.center).norm(), 2.0, epsilon = 1e-4);
+            assert_abs_diff_eq!(*point, sphere.center + 2.0 * *normal, epsilon = 1e-4);
+            assert_abs_diff_eq!(*pdf, 1.0 / (16.0 * PI), epsilon = 1e-6);
+        }
+        let mean = samples
+            .iter()
+            .fold(Vect3(0.0, 0.0, 0.0), |acc, (point, _, _)| {
+                acc + (*point - sphere.center)
+            })
+            / (samples.len() as f32);
+        assert!(mean.norm() < 0.1);
+    }
+
+    #[test]
+    fn test_moving_sphere() {
+        let sphere = MovingSphere {
+            start: Point3(0.0, 0.0, 2.0),
+            end: Point3(0.0, 1.0, 2.0),
+            radius: 0.5,
+        };
+        let center = |time: f32| {
+            let ray = Ray::new(Point3(0.0, 0.0, 0.0), Vect3(0.0, time, 2.0)).at_time(time);
+            let hits = sphere.intersected_by(&ray, 0.0..f32::INFINITY);
+            hits[0].point() - 0.5 * hits[0].normal()
+        };
+
+        assert_abs_diff_eq!(center(0.0), sphere.start, epsilon = 1e-5);
+        assert_abs_diff_eq!(center(1.0), sphere.end, epsilon = 1e-5);
+        let bounds = sphere.bounding_box();
+        assert_eq!(bounds.min, Point3(-0.5, -0.5, 1.5));
+        assert_eq!(bounds.max, Point3(0.5, 1.5, 2.5));
+
+        // At rest outside of `[0, 1]`, so still within the bounds.
+        assert_eq!(sphere.at(-1.0), sphere.at(0.0));
+        assert_eq!(sphere.at(2.0), sphere.at(1.0));
+    }
 }
@@ -0,0 +1,207 @@
+use super::{Aabb, Intersection, Surface};
+use crate::types::{Mat4, Point3, Ray, Vect3};
+use std::ops::Range;
+
+/// A surface placed in the scene by an affine transform.
+///
+/// Rays are mapped into the object space of the inner surface, and any
+/// intersections mapped back into world space. This allows a single surface
+/// (e.g. a mesh) to be reused at multiple positions and orientations.
+pub struct Transformed<S: Surface> {
+    surface: S,
+    transform: Mat4,
+    inverse: Mat4,
+}
+
+impl<S: Surface> Transformed<S> {
+    /// Construct a transformed surface.
+    ///
+    /// The transform must be invertible, or this function will panic.
+    ///
+    /// # Arguments
+    ///
+    /// * `surface` - the surface, in object space
+    /// * `transform` - the transform from object space to world space
+    pub fn new(surface: S, transform: Mat4) -> Transformed<S> {
+        let inverse = transform
+            .inverse()
+            .expect("Surface transforms must be invertible");
+        Transformed {
+            surface,
+            transform,
+            inverse,
+        }
+    }
+
+    /// Construct a surface translated by an offset.
+    pub fn translate(surface: S, offset: Vect3) -> Transformed<S> {
+        Transformed::new(surface, Mat4::translation(offset))
+    }
+
+    /// Construct a surface rotated around the y axis, by an angle in radians.
+    pub fn rotate_y(surface: S, angle: f32) -> Transformed<S> {
+        Transformed::new(surface, Mat4::rotation_y(angle))
+    }
+
+    /// Get the surface, in object space.
+    pub fn surface(&self) -> &S {
+        &self.surface
+    }
+
+    /// Get the transform from object space to world space.
+    pub fn transform(&self) -> Mat4 {
+        self.transform
+    }
+}
+
+impl<S: Surface> Surface for Transformed<S> {
+    fn intersected_by(&self, ray: &Ray, filter: Range<f32>) -> Vec<Intersection> {
+        // Distances along the object space ray are scaled by the transform.
+        let scale = self.inverse.transform_vector(ray.direction()).norm();
+        let local = self.inverse.transform_ray(ray);
+        let filter = (filter.start * scale)..(filter.end * scale);
+        // Normals are transformed by the inverse transpose.
+        let normals = self.inverse.transpose();
+        self.surface
+            .intersected_by(&local, filter)
+            .into_iter()
+            .map(|hit| {
+                let point = self.transform.transform_point(hit.point());
+                let normal = normals.transform_vector(hit.normal());
+                // The stored normal opposes the ray, so flip it back for back faces.
+                let normal = if hit.front_face() { normal } else { -normal };
+                Intersection::with_uv(ray, point, normal, hit.uv())
+            })
+            .collect()
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let bounds = self.surface.bounding_box();
+        if !(bounds.min - bounds.max).norm().is_finite() {
+            let infinity = f32::INFINITY;
+            return Aabb {
+                min: Point3(-infinity, -infinity, -infinity),
+                max: Point3(infinity, infinity, infinity),
+            };
+        }
+        let corners = [0, 1, 2, 3, 4, 5, 6, 7].map(|corner| {
+            let pick = |bit: usize, min: f32, max: f32| if corner & bit == 0 { min } else { max };
+            self.transform.transform_point(Point3(
+                pick(1, bounds.min.x(), bounds.max.x()),
+                pick(2, bounds.min.y(), bounds.max.y()),
+                pick(4, bounds.min.z(), bounds.max.z()),
+            ))
+        });
+        Aabb::from_points(&corners)
+    }
+
+    /// Compute the area of the transformed surface.
+    ///
+    /// This is only exact for rigid transforms with a uniform scaling.
+    fn area(&self) -> f32 {
+        let linear = self.transform.linear();
+        let determinant = linear.row(0).dot(linear.row(1).cross(linear.row(2)));
+        self.surface.area() * determinant.abs().powf(2.0 / 3.0)
+    }
+
+    fn contains_point(&self, point: Point3) -> bool {
+        self.surface
+            .contains_point(self.inverse.transform_point(point))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::surfaces::{Sphere, Triangle};
+    use approx::assert_abs_diff_eq;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_translated_sphere() {
+        let unit = Sphere {
+            center: Point3::zero(),
+            radius: 1.0,
+        };
+        let translated = Transformed::new(unit, Mat4::translation(Vect3(0.0, 0.0, 5.0)));
+        let sphere = Sphere {
+            center: Point3(0.0, 0.0, 5.0),
+            radius: 1.0,
+        };
+
+        for ray in [
+            Ray::new(Point3::zero(), Vect3(0.0, 0.0, 1.0)),
+            Ray::new(Point3(0.5, 0.0, 0.0), Vect3(0.0, 0.1, 1.0)),
+            Ray::new(Point3(0.0, 0.0, 5.0), Vect3(1.0, 1.0, 0.0)),
+            Ray::new(Point3(3.0, 0.0, 0.0), Vect3(0.0, 0.0, 1.0)),
+        ] {
+            let expected = sphere.intersected_by(&ray, 0.001..f32::INFINITY);
+            let hits = translated.intersected_by(&ray, 0.001..f32::INFINITY);
+            assert_eq!(hits.len(), expected.len());
+            for (hit, expected) in hits.iter().zip(expected.iter()) {
+                assert_abs_diff_eq!(hit.point(), expected.point(), epsilon = 1e-5);
+                assert_abs_diff_eq!(hit.normal(), expected.normal(), epsilon = 1e-5);
+                assert_abs_diff_eq!(hit.distance(), expected.distance(), epsilon = 1e-5);
+                assert_eq!(hit.front_face(), expected.front_face());
+            }
+        }
+        assert_eq!(translated.bounding_box(), sphere.bounding_box());
+        assert!(translated.contains_point(Point3(0.0, 0.5, 5.0)));
+        assert!(!translated.contains_point(Point3::zero()));
+    }
+
+    #[test]
+    fn test_translate() {
+        let sphere = Sphere {
+            center: Point3(0.0, 1.0, 0.0),
+            radius: 1.0,
+        };
+        let offset = Vect3(2.0, -1.0, 3.0);
+        let translated = Transformed::translate(sphere, offset);
+        let ray = Ray::new(Point3(0.3, 1.2, -5.0), Vect3(0.0, 0.0, 1.0));
+        let moved = Ray::new(ray.origin() + offset, ray.direction());
+
+        let hits = sphere.intersected_by(&ray, 0.0..f32::INFINITY);
+        let moved_hits = translated.intersected_by(&moved, 0.0..f32::INFINITY);
+        assert_eq!(moved_hits.len(), 2);
+        for (hit, moved_hit) in hits.iter().zip(moved_hits.iter()) {
+            assert_abs_diff_eq!(moved_hit.point(), hit.point() + offset, epsilon = 1e-5);
+            assert_abs_diff_eq!(moved_hit.normal(), hit.normal(), epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_rotate_y() {
+        let triangle = Triangle::new(
+            Point3(0.0, 0.0, 1.0),
+            Point3(1.0, 0.0, 1.0),
+            Point3(0.0, 1.0, 1.0),
+        );
+        // A quarter turn moves the triangle from `+z` to `+x`.
+        let rotated = Transformed::rotate_y(triangle, std::f32::consts::FRAC_PI_2);
+        let ray = Ray::new(Point3(5.0, 0.2, -0.2), Vect3(-1.0, 0.0, 0.0));
+
+        let hits = rotated.intersected_by(&ray, 0.0..f32::INFINITY);
+        assert_eq!(hits.len(), 1);
+        assert_abs_diff_eq!(hits[0].point(), Point3(1.0, 0.2, -0.2), epsilon = 1e-5);
+        assert_abs_diff_eq!(hits[0].normal(), Vect3(1.0, 0.0, 0.0), epsilon = 1e-5);
+        assert!(triangle.intersected_by(&ray, 0.0..f32::INFINITY).is_empty());
+    }
+
+    #[test]
+    fn test_scaled_sphere() {
+        let unit = Sphere {
+            center: Point3::zero(),
+            radius: 1.0,
+        };
+        let scaled = Transformed::new(unit, Mat4::scale(Vect3(2.0, 2.0, 2.0)));
+        let ray = Ray::new(Point3(0.0, 0.0, -5.0), Vect3(0.0, 0.0, 1.0));
+
+        let hits = scaled.intersected_by(&ray, 0.0..f32::INFINITY);
+        assert_eq!(hits.len(), 2);
+        assert_abs_diff_eq!(hits[0].point(), Point3(0.0, 0.0, -2.0), epsilon = 1e-5);
+        assert_abs_diff_eq!(hits[0].distance(), 3.0, epsilon = 1e-5);
+        assert!(scaled.intersected_by(&ray, 0.0..2.5).is_empty());
+        assert_abs_diff_eq!(scaled.area(), 4.0 * unit.area(), epsilon = 1e-3);
+    }
+}
@@ -0,0 +1,168 @@
+use super::{Aabb, Intersection, Surface};
+use crate::types::{Point3, Ray, Vect3};
+use std::ops::Range;
+
+/// Intersect a ray with a triangle, returning the distance along the ray
+/// and the barycentric weights of the second and third vertices.
+///
+/// See <https://en.wikipedia.org/wiki/M%C3%B6ller%E2%80%93Trumbore_intersection_algorithm>.
+pub(super) fn intersect_triangle(ray: &Ray, vertices: [Point3; 3]) -> Option<(f32, f32, f32)> {
+    let edge1 = vertices[1] - vertices[0];
+    let edge2 = vertices[2] - vertices[0];
+    let p = ray.direction().cross(edge2);
+    let determinant = edge1.dot(p);
+    if determinant.abs() < f32::EPSILON {
+        return None;
+    }
+    let offset = ray.origin() - vertices[0];
+    let u = offset.dot(p) / determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = offset.cross(edge1);
+    let v = ray.direction().dot(q) / determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    Some((edge2.dot(q) / determinant, u, v))
+}
+
+/// An intersectable triangle.
+///
+/// If per-vertex normals are given, the normal at an intersection is
+/// interpolated between them (i.e. smooth shading). Otherwise, the
+/// geometric normal of the triangle is used.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Triangle {
+    pub vertices: [Point3; 3],
+    pub normals: Option<[Vect3; 3]>,
+}
+
+impl Triangle {
+    /// Construct a flat-shaded triangle.
+    pub fn new(a: Point3, b: Point3, c: Point3) -> Triangle {
+        Triangle {
+            vertices: [a, b, c],
+            normals: None,
+        }
+    }
+
+    /// Construct a smooth-shaded triangle with per-vertex normals.
+    pub fn with_normals(a: Point3, b: Point3, c: Point3, normals: [Vect3; 3]) -> Triangle {
+        Triangle {
+            vertices: [a, b, c],
+            normals: Some(normals),
+        }
+    }
+
+    /// Get the geometric normal of the triangle.
+    pub fn face_normal(&self) -> Vect3 {
+        let [a, b, c] = self.vertices;
+        (b - a).cross(c - a).normalize()
+    }
+}
+
+impl Surface for Triangle {
+    fn intersected_by(&self, ray: &Ray, filter: Range<f32>) -> Vec<Intersection> {
+        match intersect_triangle(ray, self.vertices) {
+            Some((distance, u, v)) if filter.contains(&distance) => {
+                let normal = match self.normals {
+                    Some([n0, n1, n2]) => (1.0 - u - v) * n0 + u * n1 + v * n2,
+                    None => self.face_normal(),
+                };
+                vec![Intersection::new(ray, ray.at(distance), normal)]
+            }
+            _ => vec![],
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::from_points(&self.vertices)
+    }
+
+    fn area(&self) -> f32 {
+        let [a, b, c] = self.vertices;
+        0.5 * (b - a).cross(c - a).norm()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use pretty_assertions::assert_eq;
+
+    fn triangle(normals: Option<[Vect3; 3]>) -> Triangle {
+        Triangle {
+            vertices: [
+                Point3(0.0, 0.0, 0.0),
+                Point3(1.0, 0.0, 0.0),
+                Point3(0.0, 1.0, 0.0),
+            ],
+            normals,
+        }
+    }
+
+    #[test]
+    fn test_flat_intersection() {
+        let ray = Ray::new(Point3(0.25, 0.25, 1.0), Vect3(0.0, 0.0, -1.0));
+
+        let expected = vec![Intersection::new(
+            &ray,
+            Point3(0.25, 0.25, 0.0),
+            Vect3(0.0, 0.0, 1.0),
+        )];
+        assert_eq!(
+            triangle(None).intersected_by(&ray, 0.0..f32::INFINITY),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_no_intersection() {
+        let ray = Ray::new(Point3(0.75, 0.75, 1.0), Vect3(0.0, 0.0, -1.0));
+
+        assert_eq!(
+            triangle(None).intersected_by(&ray, 0.0..f32::INFINITY),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_smooth_normals() {
+        let normals = [
+            Vect3(0.0, 0.0, 1.0),
+            Vect3(1.0, 0.0, 1.0).normalize(),
+            Vect3(0.0, 1.0, 1.0).normalize(),
+        ];
+        let smooth = triangle(Some(normals));
+        let at_vertex = Ray::new(Point3(0.0, 0.0, 1.0), Vect3(0.0, 0.0, -1.0));
+        let at_centroid = Ray::new(Point3(1.0 / 3.0, 1.0 / 3.0, 1.0), Vect3(0.0, 0.0, -1.0));
+
+        let hits = smooth.intersected_by(&at_vertex, 0.0..f32::INFINITY);
+        assert_eq!(hits.len(), 1);
+        assert_abs_diff_eq!(hits[0].normal(), normals[0], epsilon = 1e-6);
+        let hits = smooth.intersected_by(&at_centroid, 0.0..f32::INFINITY);
+        let average = (normals[0] + normals[1] + normals[2]).normalize();
+        assert_eq!(hits.len(), 1);
+        assert_abs_diff_eq!(hits[0].normal(), average, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_area() {
+        let skewed = Triangle::new(
+            Point3(1.0, 0.0, 0.0),
+            Point3(3.0, 1.0, 0.0),
+            Point3(1.0, 0.0, 2.0),
+        );
+        let [a, b, c] = skewed.vertices;
+
+        assert_eq!(triangle(None).area(), 0.5);
+        assert_abs_diff_eq!(
+            skewed.area(),
+            0.5 * (b - a).cross(c - a).norm(),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(skewed.area(), 5.0_f32.sqrt(), epsilon = 1e-6);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::image::Pixel;
+use std::str::FromStr;
+
+/// A tone-mapping operator, compressing high dynamic range pixel values
+/// into the displayable `[0, 1]` range.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ToneMap {
+    /// No tone mapping (values above `1.0` are clipped when saved).
+    #[default]
+    None,
+    /// The simple Reinhard operator, `c / (1 + c)`.
+    Reinhard,
+    /// The fitted ACES filmic curve.
+    Aces,
+}
+
+impl FromStr for ToneMap {
+    type Err = String;
+
+    /// Parse a tone-mapping operator from its (case-insensitive) name, i.e.
+    /// one of `none`, `reinhard` or `aces`.
+    fn from_str(name: &str) -> Result<ToneMap, String> {
+        match name.to_lowercase().as_str() {
+            "none" => Ok(ToneMap::None),
+            "reinhard" => Ok(ToneMap::Reinhard),
+            "aces" => Ok(ToneMap::Aces),
+            _ => Err(format!("Unknown tone-mapping operator: {}", name)),
+        }
+    }
+}
+
+/// Apply a tone-mapping operator to a pixel, channel by channel.
+///
+/// # Arguments
+///
+/// * `pixel` - the (linear) pixel to tone-map
+/// * `op` - the operator to apply
+pub fn apply(pixel: Pixel, op: ToneMap) -> Pixel {
+    let map = |c: f32| match op {
+        ToneMap::None => c,
+        ToneMap::Reinhard => c / (1.0 + c),
+        // See <https://knarkowicz.wordpress.com/2016/01/06/aces-filmic-tone-mapping-curve/>.
+        ToneMap::Aces => ((c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14)).clamp(0.0, 1.0),
+    };
+    Pixel(map(pixel.red()), map(pixel.green()), map(pixel.blue()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("none".parse(), Ok(ToneMap::None));
+        assert_eq!("Reinhard".parse(), Ok(ToneMap::Reinhard));
+        assert_eq!("aces".parse(), Ok(ToneMap::Aces));
+        assert!("filmic".parse::<ToneMap>().is_err());
+    }
+
+    #[test]
+    fn test_none_is_identity() {
+        let pixel = Pixel(4.0, 0.5, 12.0);
+        assert_eq!(apply(pixel, ToneMap::None), pixel);
+    }
+
+    #[test]
+    fn test_bright_values_map_into_range() {
+        let pixel = Pixel(4.0, 0.5, 120.0);
+        for op in [ToneMap::Reinhard, ToneMap::Aces] {
+            let mapped = apply(pixel, op);
+            for channel in [mapped.red(), mapped.green(), mapped.blue()] {
+                assert!((0.0..=1.0).contains(&channel));
+            }
+            assert!(mapped.red() > mapped.green());
+            assert!(mapped.blue() >= mapped.red());
+        }
+        assert_eq!(apply(pixel, ToneMap::Reinhard).red(), 0.8);
+    }
+}
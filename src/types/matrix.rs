@@ -0,0 +1,276 @@
+use super::{Point3, Ray, Vect3};
+use auto_ops::*;
+
+/// A 3×3 matrix, stored in row-major order.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Mat3(pub [[f32; 3]; 3]);
+
+impl Mat3 {
+    /// Return the identity matrix.
+    pub fn identity() -> Mat3 {
+        Mat3([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    /// Construct a matrix from its three rows.
+    pub fn from_rows(rows: [Vect3; 3]) -> Mat3 {
+        Mat3(rows.map(|row| [row.x(), row.y(), row.z()]))
+    }
+
+    /// Get a row of the matrix.
+    pub fn row(&self, index: usize) -> Vect3 {
+        let [x, y, z] = self.0[index];
+        Vect3(x, y, z)
+    }
+
+    /// Get a column of the matrix.
+    pub fn column(&self, index: usize) -> Vect3 {
+        Vect3(self.0[0][index], self.0[1][index], self.0[2][index])
+    }
+
+    /// Return the transpose of the matrix.
+    pub fn transpose(&self) -> Mat3 {
+        Mat3::from_rows([self.column(0), self.column(1), self.column(2)])
+    }
+}
+
+impl_op_ex!(*|a: &Mat3, b: &Vect3| -> Vect3 {
+    Vect3(a.row(0).dot(*b), a.row(1).dot(*b), a.row(2).dot(*b))
+});
+
+impl_op_ex!(*|a: &Mat3, b: &Mat3| -> Mat3 {
+    let mut product = Mat3::default();
+    for (i, row) in product.0.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = a.row(i).dot(b.column(j));
+        }
+    }
+    product
+});
+
+/// A 4×4 matrix of an affine transform, stored in row-major order.
+///
+/// Points and vectors are transformed as column vectors, so in a product
+/// `a * b` the transform `b` is applied first.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Mat4(pub [[f32; 4]; 4]);
+
+impl Mat4 {
+    /// Return the identity matrix.
+    pub fn identity() -> Mat4 {
+        Mat4::from_linear(Mat3::identity())
+    }
+
+    /// Construct a transform from a linear (3×3) transform.
+    pub fn from_linear(linear: Mat3) -> Mat4 {
+        let mut matrix = Mat4::default();
+        for (i, row) in linear.0.iter().enumerate() {
+            matrix.0[i][..3].copy_from_slice(row);
+        }
+        matrix.0[3][3] = 1.0;
+        matrix
+    }
+
+    /// Construct a translation by an offset.
+    pub fn translation(offset: Vect3) -> Mat4 {
+        let mut matrix = Mat4::identity();
+        matrix.0[0][3] = offset.x();
+        matrix.0[1][3] = offset.y();
+        matrix.0[2][3] = offset.z();
+        matrix
+    }
+
+    /// Construct a rotation around the x axis, by an angle in radians.
+    pub fn rotation_x(angle: f32) -> Mat4 {
+        let (sin, cos) = angle.sin_cos();
+        Mat4::from_linear(Mat3([[1.0, 0.0, 0.0], [0.0, cos, -sin], [0.0, sin, cos]]))
+    }
+
+    /// Construct a rotation around the y axis, by an angle in radians.
+    pub fn rotation_y(angle: f32) -> Mat4 {
+        let (sin, cos) = angle.sin_cos();
+        Mat4::from_linear(Mat3([[cos, 0.0, sin], [0.0, 1.0, 0.0], [-sin, 0.0, cos]]))
+    }
+
+    /// Construct a rotation around the z axis, by an angle in radians.
+    pub fn rotation_z(angle: f32) -> Mat4 {
+        let (sin, cos) = angle.sin_cos();
+        Mat4::from_linear(Mat3([[cos, -sin, 0.0], [sin, cos, 0.0], [0.0, 0.0, 1.0]]))
+    }
+
+    /// Construct a scaling by a factor along each axis.
+    pub fn scale(factors: Vect3) -> Mat4 {
+        Mat4::from_linear(Mat3([
+            [factors.x(), 0.0, 0.0],
+            [0.0, factors.y(), 0.0],
+            [0.0, 0.0, factors.z()],
+        ]))
+    }
+
+    /// Get the linear (3×3) part of the transform.
+    pub fn linear(&self) -> Mat3 {
+        Mat3([0, 1, 2].map(|i| [self.0[i][0], self.0[i][1], self.0[i][2]]))
+    }
+
+    /// Return the transpose of the matrix.
+    pub fn transpose(&self) -> Mat4 {
+        let mut transpose = Mat4::default();
+        for (i, row) in self.0.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                transpose.0[j][i] = *value;
+            }
+        }
+        transpose
+    }
+
+    /// Return the inverse of the matrix, or `None` if it is singular.
+    pub fn inverse(&self) -> Option<Mat4> {
+        // Gauss-Jordan elimination with partial pivoting.
+        let mut matrix = self.0;
+        let mut inverse = Mat4::identity().0;
+        for column in 0..4 {
+            let pivot = (column..4)
+                .max_by(|a, b| {
+                    matrix[*a][column]
+                        .abs()
+                        .total_cmp(&matrix[*b][column].abs())
+                })
+                .unwrap();
+            if matrix[pivot][column].abs() < f32::EPSILON {
+                return None;
+            }
+            matrix.swap(column, pivot);
+            inverse.swap(column, pivot);
+            let scale = matrix[column][column].recip();
+            for j in 0..4 {
+                matrix[column][j] *= scale;
+                inverse[column][j] *= scale;
+            }
+            for row in (0..4).filter(|row| *row != column) {
+                let factor = matrix[row][column];
+                for j in 0..4 {
+                    matrix[row][j] -= factor * matrix[column][j];
+                    inverse[row][j] -= factor * inverse[column][j];
+                }
+            }
+        }
+        Some(Mat4(inverse))
+    }
+
+    /// Transform a point (applying the translation).
+    pub fn transform_point(&self, point: Point3) -> Point3 {
+        let [x, y, z] = [0, 1, 2].map(|i| {
+            let row = self.0[i];
+            row[0] * point.x() + row[1] * point.y() + row[2] * point.z() + row[3]
+        });
+        Point3(x, y, z)
+    }
+
+    /// Transform a vector (ignoring the translation).
+    pub fn transform_vector(&self, vector: Vect3) -> Vect3 {
+        self.linear() * vector
+    }
+
+    /// Transform a ray, keeping the time at which it is cast.
+    ///
+    /// Note that the direction of the transformed ray is normalized, so
+    /// distances along it are only preserved by rigid transforms.
+    pub fn transform_ray(&self, ray: &Ray) -> Ray {
+        Ray::new(
+            self.transform_point(ray.origin()),
+            self.transform_vector(ray.direction()),
+        )
+        .at_time(ray.time())
+    }
+}
+
+impl_op_ex!(*|a: &Mat4, b: &Mat4| -> Mat4 {
+    let mut product = Mat4::default();
+    for (i, row) in product.0.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a.0[i][k] * b.0[k][j]).sum();
+        }
+    }
+    product
+});
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use pretty_assertions::assert_eq;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_mat3_ops() {
+        let matrix = Mat3([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]]);
+        let vect = Vect3(1.0, 0.0, -1.0);
+
+        assert_eq!(Mat3::identity() * vect, vect);
+        assert_eq!(matrix * vect, Vect3(-2.0, -2.0, -2.0));
+        assert_eq!(Mat3::identity() * matrix, matrix);
+        assert_eq!(matrix * Mat3::identity(), matrix);
+        assert_eq!(
+            matrix * matrix.transpose(),
+            Mat3([
+                [14.0, 32.0, 50.0],
+                [32.0, 77.0, 122.0],
+                [50.0, 122.0, 194.0]
+            ])
+        );
+        assert_eq!(matrix.transpose().row(0), matrix.column(0));
+    }
+
+    #[test]
+    fn test_mat4_transforms() {
+        let point = Point3(1.0, 2.0, 3.0);
+        let vector = Vect3(1.0, 2.0, 3.0);
+        let translation = Mat4::translation(Vect3(0.0, 0.0, 5.0));
+
+        assert_eq!(translation.transform_point(point), Point3(1.0, 2.0, 8.0));
+        assert_eq!(translation.transform_vector(vector), vector);
+        assert_eq!(
+            Mat4::scale(Vect3(2.0, 1.0, -1.0)).transform_point(point),
+            Point3(2.0, 2.0, -3.0)
+        );
+        assert_abs_diff_eq!(
+            Mat4::rotation_x(FRAC_PI_2).transform_vector(Vect3(0.0, 1.0, 0.0)),
+            Vect3(0.0, 0.0, 1.0),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(
+            Mat4::rotation_y(FRAC_PI_2).transform_vector(Vect3(0.0, 0.0, 1.0)),
+            Vect3(1.0, 0.0, 0.0),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(
+            Mat4::rotation_z(FRAC_PI_2).transform_vector(Vect3(1.0, 0.0, 0.0)),
+            Vect3(0.0, 1.0, 0.0),
+            epsilon = 1e-6
+        );
+        // The right-hand transform is applied first.
+        let combined = translation * Mat4::scale(Vect3(2.0, 2.0, 2.0));
+        assert_eq!(combined.transform_point(point), Point3(2.0, 4.0, 11.0));
+    }
+
+    #[test]
+    fn test_mat4_inverse() {
+        let transform = Mat4::translation(Vect3(1.0, -2.0, 5.0))
+            * Mat4::rotation_y(0.7)
+            * Mat4::scale(Vect3(2.0, 0.5, 3.0));
+        let inverse = transform.inverse().unwrap();
+        let point = Point3(0.3, -1.2, 4.0);
+
+        for (i, row) in (transform * inverse).0.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert_abs_diff_eq!(*value, expected, epsilon = 1e-5);
+            }
+        }
+        assert_abs_diff_eq!(
+            inverse.transform_point(transform.transform_point(point)),
+            point,
+            epsilon = 1e-5
+        );
+        assert_eq!(Mat4::scale(Vect3(1.0, 0.0, 1.0)).inverse(), None);
+    }
+}
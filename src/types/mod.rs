@@ -1,9 +1,13 @@
 /// Useful types for use in a raytracer.
+mod matrix;
+mod onb;
 mod point;
 mod ray;
 mod vect;
 
 // Exports.
+pub use matrix::{Mat3, Mat4};
+pub use onb::Onb;
 pub use point::Point3;
 pub use ray::Ray;
 pub use vect::Vect3;
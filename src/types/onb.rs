@@ -0,0 +1,83 @@
+use super::Vect3;
+
+/// An orthonormal basis, i.e. a right-handed set of three orthogonal unit
+/// vectors `u`, `v` and `w`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Onb {
+    u: Vect3,
+    v: Vect3,
+    w: Vect3,
+}
+
+impl Onb {
+    /// Construct an orthonormal basis with a given `w` axis.
+    ///
+    /// The `u` and `v` axes are chosen arbitrarily, using whichever helper
+    /// axis is furthest from being parallel to `w`.
+    pub fn from_w(normal: Vect3) -> Onb {
+        let w = normal.normalize();
+        let helper = if w.x().abs() > 0.9 {
+            Vect3(0.0, 1.0, 0.0)
+        } else {
+            Vect3(1.0, 0.0, 0.0)
+        };
+        let v = w.cross(helper).normalize();
+        let u = v.cross(w);
+        Onb { u, v, w }
+    }
+
+    /// The `u` axis of the basis.
+    pub fn u(&self) -> Vect3 {
+        self.u
+    }
+
+    /// The `v` axis of the basis.
+    pub fn v(&self) -> Vect3 {
+        self.v
+    }
+
+    /// The `w` axis of the basis.
+    pub fn w(&self) -> Vect3 {
+        self.w
+    }
+
+    /// Transform a vector from local coordinates (in this basis) to world
+    /// coordinates.
+    pub fn local(&self, a: Vect3) -> Vect3 {
+        a.x() * self.u + a.y() * self.v + a.z() * self.w
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_orthonormal() {
+        for normal in [
+            Vect3(0.0, 0.0, 1.0),
+            Vect3(1.0, 0.0, 0.0),
+            Vect3(-1.0, 0.0, 0.0),
+            Vect3(1.0, 1e-6, 0.0),
+            Vect3(0.0, -1.0, 0.0),
+            Vect3(1.0, 2.0, -3.0),
+            Vect3(0.95, 0.1, 0.0),
+        ] {
+            let onb = Onb::from_w(normal);
+            for axis in [onb.u(), onb.v(), onb.w()] {
+                assert_abs_diff_eq!(axis.norm(), 1.0, epsilon = 1e-6);
+            }
+            assert_abs_diff_eq!(onb.u().dot(onb.v()), 0.0, epsilon = 1e-6);
+            assert_abs_diff_eq!(onb.v().dot(onb.w()), 0.0, epsilon = 1e-6);
+            assert_abs_diff_eq!(onb.w().dot(onb.u()), 0.0, epsilon = 1e-6);
+            assert_abs_diff_eq!(onb.u().cross(onb.v()), onb.w(), epsilon = 1e-6);
+            assert_abs_diff_eq!(onb.w(), normal.normalize(), epsilon = 1e-6);
+            assert_abs_diff_eq!(
+                onb.local(Vect3(0.0, 0.0, 2.0)),
+                2.0 * onb.w(),
+                epsilon = 1e-6
+            );
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use super::Vect3;
 use auto_ops::*;
+use serde::{Deserialize, Serialize};
+use std::ops::Index;
 use std::{f32, fmt};
 
 /// A point in ℝ³.
-#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Point3(pub f32, pub f32, pub f32);
 
 impl Point3 {
@@ -26,6 +28,38 @@ impl Point3 {
     pub fn z(&self) -> f32 {
         self.2
     }
+
+    /// The coordinate of the point along an axis (`0`, `1` or `2` for `x`, `y`
+    /// or `z`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `axis` is not a valid axis.
+    pub fn component(&self, axis: usize) -> f32 {
+        self[axis]
+    }
+
+    /// Linearly interpolate between this point (at `0.0`) and another (at `1.0`).
+    pub fn lerp(self, other: Point3, t: f32) -> Point3 {
+        Point3(
+            self.0 * (1.0 - t) + other.0 * t,
+            self.1 * (1.0 - t) + other.1 * t,
+            self.2 * (1.0 - t) + other.2 * t,
+        )
+    }
+}
+
+impl Index<usize> for Point3 {
+    type Output = f32;
+
+    fn index(&self, axis: usize) -> &f32 {
+        match axis {
+            0 => &self.0,
+            1 => &self.1,
+            2 => &self.2,
+            _ => panic!("Axis index out of range: {}", axis),
+        }
+    }
 }
 
 impl fmt::Display for Point3 {
@@ -54,7 +88,7 @@ impl_op_ex!(-|a: &Point3, b: &Point3| -> Vect3 {
     )
 });
 
-#[cfg(test)]
+#[cfg(any(test, feature = "approx"))]
 impl approx::AbsDiffEq for Point3 {
     type Epsilon = f32;
 
@@ -67,7 +101,7 @@ impl approx::AbsDiffEq for Point3 {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "approx"))]
 impl approx::UlpsEq for Point3 {
     fn default_max_ulps() -> u32 {
         f32::default_max_ulps()
@@ -79,9 +113,9 @@ impl approx::UlpsEq for Point3 {
         epsilon: <Point3 as approx::AbsDiffEq>::Epsilon,
         max_ulps: u32,
     ) -> bool {
-        f32::ulps_eq(&self.x(), &other.x(), epsilon.clone(), max_ulps)
-            && f32::ulps_eq(&self.y(), &other.y(), epsilon.clone(), max_ulps)
-            && f32::ulps_eq(&self.z(), &other.z(), epsilon.clone(), max_ulps)
+        f32::ulps_eq(&self.x(), &other.x(), epsilon, max_ulps)
+            && f32::ulps_eq(&self.y(), &other.y(), epsilon, max_ulps)
+            && f32::ulps_eq(&self.z(), &other.z(), epsilon, max_ulps)
     }
 }
 
@@ -90,6 +124,22 @@ mod test {
     use super::*;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn test_point3_index() {
+        let point = Point3(1.0, 2.0, 3.0);
+
+        assert_eq!(point[0], point.x());
+        assert_eq!(point[1], point.y());
+        assert_eq!(point[2], point.z());
+        assert_eq!(point.component(1), 2.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_point3_index_out_of_range() {
+        Point3(1.0, 2.0, 3.0).component(3);
+    }
+
     #[test]
     fn test_point3_ops() {
         let zero = Point3::zero();
@@ -102,6 +152,16 @@ mod test {
         assert_eq!(point - vect, zero);
     }
 
+    #[test]
+    fn test_point3_lerp() {
+        let a = Point3(0.1, -2.0, 3.0);
+        let b = Point3(0.7, 4.0, -1.0);
+
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 0.5), Point3(0.4, 1.0, 1.0));
+        assert_eq!(a.lerp(b, 1.0), b);
+    }
+
     #[test]
     fn test_point3_assign_ops() {
         let orig = Point3::zero();
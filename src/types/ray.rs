@@ -5,6 +5,7 @@ use super::{Point3, Vect3};
 pub struct Ray {
     origin: Point3,
     direction: Vect3,
+    time: f32,
 }
 
 impl Ray {
@@ -13,9 +14,15 @@ impl Ray {
         Ray {
             origin,
             direction: direction.normalize(),
+            time: 0.0,
         }
     }
 
+    /// Get the same ray, but cast at a specific point in time.
+    pub fn at_time(self, time: f32) -> Ray {
+        Ray { time, ..self }
+    }
+
     /// Get a specific point along the half-line.
     pub fn at(&self, distance: f32) -> Point3 {
         self.origin + (distance * self.direction)
@@ -30,6 +37,11 @@ impl Ray {
     pub fn direction(&self) -> Vect3 {
         self.direction
     }
+
+    /// Get the time at which this ray was cast.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
 }
 
 #[cfg(test)]
@@ -1,8 +1,25 @@
 use auto_ops::*;
+use serde::{Deserialize, Serialize};
+use std::ops::Index;
 use std::{f32, fmt};
 
 /// A vector in ℝ³.
-#[derive(Debug, Default, Clone, Copy, PartialEq)]
+///
+/// With the `approx` feature enabled (the default), vectors can be compared
+/// approximately using the macros from the `approx` crate.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "approx")]
+/// # {
+/// use approx::assert_abs_diff_eq;
+/// use raytrust::Vect3;
+/// let vect = Vect3(1.0, 2.0, 3.0);
+/// assert_abs_diff_eq!(vect.normalize() * vect.norm(), vect, epsilon = 1e-6);
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Vect3(pub f32, pub f32, pub f32);
 
 impl Vect3 {
@@ -26,6 +43,16 @@ impl Vect3 {
         self.2
     }
 
+    /// The coordinate of the vector along an axis (`0`, `1` or `2` for `x`, `y`
+    /// or `z`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `axis` is not a valid axis.
+    pub fn component(&self, axis: usize) -> f32 {
+        self[axis]
+    }
+
     /// Return the dot product of two vectors.
     pub fn dot(self, other: Vect3) -> f32 {
         (self.x() * other.x()) + (self.y() * other.y()) + (self.z() * other.z())
@@ -42,7 +69,14 @@ impl Vect3 {
 
     /// Return the norm of the vector.
     pub fn norm(self) -> f32 {
-        self.dot(self).sqrt()
+        self.norm_squared().sqrt()
+    }
+
+    /// Return the squared norm of the vector.
+    ///
+    /// This avoids the square root of `norm`, e.g. when comparing lengths.
+    pub fn norm_squared(self) -> f32 {
+        self.dot(self)
     }
 
     /// Return a normalized copy of the vector.
@@ -54,6 +88,68 @@ impl Vect3 {
     pub fn project(self, other: Vect3) -> Vect3 {
         (self.dot(other) / self.dot(self)) * self
     }
+
+    /// Linearly interpolate between this vector (at `0.0`) and another (at `1.0`).
+    pub fn lerp(self, other: Vect3, t: f32) -> Vect3 {
+        self * (1.0 - t) + other * t
+    }
+
+    /// Return the component-wise minimum of two vectors.
+    pub fn min(self, other: Vect3) -> Vect3 {
+        Vect3(
+            self.0.min(other.0),
+            self.1.min(other.1),
+            self.2.min(other.2),
+        )
+    }
+
+    /// Return the component-wise maximum of two vectors.
+    pub fn max(self, other: Vect3) -> Vect3 {
+        Vect3(
+            self.0.max(other.0),
+            self.1.max(other.1),
+            self.2.max(other.2),
+        )
+    }
+
+    /// Return the component-wise absolute value of the vector.
+    pub fn abs(self) -> Vect3 {
+        Vect3(self.0.abs(), self.1.abs(), self.2.abs())
+    }
+
+    /// Reflect the vector at a surface with a given (unit) normal.
+    pub fn reflect(self, normal: Vect3) -> Vect3 {
+        self - 2.0 * self.dot(normal) * normal
+    }
+
+    /// Refract the (unit) vector at a surface with a given (unit) normal.
+    ///
+    /// The normal must oppose the vector, and `ratio` is the ratio between
+    /// the refractive indices on the incident and the far side. Returns
+    /// `None` if the vector is totally internally reflected instead.
+    pub fn refract(self, normal: Vect3, ratio: f32) -> Option<Vect3> {
+        let cos_theta = self.dot(-normal).min(1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        if ratio * sin_theta > 1.0 {
+            return None;
+        }
+        let orthogonal = ratio * (self + cos_theta * normal);
+        let parallel = -(1.0 - orthogonal.dot(orthogonal)).abs().sqrt() * normal;
+        Some(orthogonal + parallel)
+    }
+}
+
+impl Index<usize> for Vect3 {
+    type Output = f32;
+
+    fn index(&self, axis: usize) -> &f32 {
+        match axis {
+            0 => &self.0,
+            1 => &self.1,
+            2 => &self.2,
+            _ => panic!("Axis index out of range: {}", axis),
+        }
+    }
 }
 
 impl fmt::Display for Vect3 {
@@ -94,7 +190,7 @@ impl_op_ex_commutative!(*|a: &Vect3, b: &f32| -> Vect3 {
 impl_op_ex!(/= |a: &mut Vect3, b: &f32| { *a = *a / b; });
 impl_op_ex!(/|a: &Vect3, b: &f32| -> Vect3 { a * b.recip()});
 
-#[cfg(test)]
+#[cfg(any(test, feature = "approx"))]
 impl approx::AbsDiffEq for Vect3 {
     type Epsilon = f32;
 
@@ -107,7 +203,7 @@ impl approx::AbsDiffEq for Vect3 {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "approx"))]
 impl approx::UlpsEq for Vect3 {
     fn default_max_ulps() -> u32 {
         f32::default_max_ulps()
@@ -119,9 +215,9 @@ impl approx::UlpsEq for Vect3 {
         epsilon: <Vect3 as approx::AbsDiffEq>::Epsilon,
         max_ulps: u32,
     ) -> bool {
-        f32::ulps_eq(&self.x(), &other.x(), epsilon.clone(), max_ulps)
-            && f32::ulps_eq(&self.y(), &other.y(), epsilon.clone(), max_ulps)
-            && f32::ulps_eq(&self.z(), &other.z(), epsilon.clone(), max_ulps)
+        f32::ulps_eq(&self.x(), &other.x(), epsilon, max_ulps)
+            && f32::ulps_eq(&self.y(), &other.y(), epsilon, max_ulps)
+            && f32::ulps_eq(&self.z(), &other.z(), epsilon, max_ulps)
     }
 }
 
@@ -130,6 +226,33 @@ mod test {
     use super::*;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn test_vect3_min_max_abs() {
+        let a = Vect3(-1.0, 2.0, -3.0);
+        let b = Vect3(0.5, -4.0, -2.0);
+
+        assert_eq!(a.min(b), Vect3(-1.0, -4.0, -3.0));
+        assert_eq!(a.max(b), Vect3(0.5, 2.0, -2.0));
+        assert_eq!(a.abs(), Vect3(1.0, 2.0, 3.0));
+        assert_eq!(b.abs(), Vect3(0.5, 4.0, 2.0));
+    }
+
+    #[test]
+    fn test_vect3_index() {
+        let vector = Vect3(1.0, 2.0, 3.0);
+
+        assert_eq!(vector[0], vector.x());
+        assert_eq!(vector[1], vector.y());
+        assert_eq!(vector[2], vector.z());
+        assert_eq!(vector.component(1), 2.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_vect3_index_out_of_range() {
+        Vect3(1.0, 2.0, 3.0).component(3);
+    }
+
     #[test]
     fn test_vect3_ops() {
         let zero = Vect3::zero();
@@ -144,6 +267,16 @@ mod test {
         assert_eq!(vect1 - vect2, -vect1);
     }
 
+    #[test]
+    fn test_vect3_lerp() {
+        let a = Vect3(0.1, -2.0, 3.0);
+        let b = Vect3(0.7, 4.0, -1.0);
+
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 0.5), Vect3(0.4, 1.0, 1.0));
+        assert_eq!(a.lerp(b, 1.0), b);
+    }
+
     #[test]
     fn test_vect3_assign_ops() {
         let orig = Vect3(1.0, 1.0, 1.0);
@@ -170,6 +303,24 @@ mod test {
         assert_ulps_eq!(vect.normalize().norm(), 1.0);
     }
 
+    #[test]
+    fn test_vect3_norm_squared() {
+        use approx::assert_relative_eq;
+        for vect in [
+            Vect3::zero(),
+            Vect3(1.0, 1.0, 1.0),
+            Vect3(3.0, -4.0, 0.0),
+            Vect3(0.1, 2.5, -7.25),
+        ] {
+            assert_relative_eq!(
+                vect.norm_squared(),
+                vect.norm().powi(2),
+                max_relative = 1e-6
+            );
+        }
+        assert_eq!(Vect3(3.0, -4.0, 0.0).norm_squared(), 25.0);
+    }
+
     #[test]
     fn test_vect3_dot() {
         let zero = Vect3::zero();
@@ -196,4 +347,47 @@ mod test {
         assert_eq!(vect3.cross(vect2), -vect1);
         assert_eq!(vect1.cross(vect3), -vect2);
     }
+
+    #[test]
+    fn test_vect3_reflect() {
+        let normal = Vect3(0.0, 0.0, -1.0);
+
+        assert_eq!(Vect3(0.0, 0.0, 1.0).reflect(normal), Vect3(0.0, 0.0, -1.0));
+        assert_eq!(Vect3(1.0, 0.0, 1.0).reflect(normal), Vect3(1.0, 0.0, -1.0));
+        assert_eq!(Vect3(1.0, 0.0, 0.0).reflect(normal), Vect3(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_vect3_refract_0_deg() {
+        let incident = Vect3(0.0, 0.0, 1.0);
+        let normal = Vect3(0.0, 0.0, -1.0);
+        let ratio = 2.0_f32.sqrt();
+
+        assert_eq!(incident.refract(normal, ratio), Some(Vect3(0.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn test_vect3_refract_45_deg() {
+        use approx::assert_abs_diff_eq;
+        let incident = Vect3(0.0, 1.0, 1.0).normalize();
+        let normal = Vect3(0.0, 0.0, -1.0);
+        let refract = |ratio: f32| incident.refract(normal, ratio).unwrap();
+
+        assert_abs_diff_eq!(refract(0.0), Vect3(0.0, 0.0, 1.0), epsilon = 0.001);
+        assert_abs_diff_eq!(
+            refract(0.9),
+            Vect3(0.0, 0.636396, 0.771362),
+            epsilon = 0.001
+        );
+        assert_abs_diff_eq!(refract(1.0), incident, epsilon = 0.001);
+    }
+
+    #[test]
+    fn test_vect3_total_internal_reflection() {
+        let incident = Vect3(0.0, 1.0, 1.0).normalize();
+        let normal = Vect3(0.0, 0.0, -1.0);
+
+        assert_eq!(incident.refract(normal, 1.5), None);
+        assert!(Vect3(0.0, 0.0, 1.0).refract(normal, 1.5).is_some());
+    }
 }